@@ -6,6 +6,6 @@ pub trait Transmitter {
     fn send_msg(&mut self, buf: &[u8]) -> Result<Self::Ok>;
 }
 
-pub trait Receiver<'storage> {
-    fn receive_msg(&'storage mut self, timeout: i32) -> Result<&'storage [u8]>;
+pub trait Receiver {
+    fn receive_msg(&mut self, timeout: i32) -> Result<&[u8]>;
 }
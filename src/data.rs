@@ -0,0 +1,365 @@
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Serialize,
+};
+
+use crate::micro_cdr;
+use crate::object_id::ObjectId;
+use crate::sample_identity::SampleIdentity;
+use crate::stream_id::StreamId;
+use crate::submessage::DataFormat;
+
+pub const MAX_SAMPLES_UNLIMITED: u16 = 0xFFFF;
+pub const MAX_ELAPSED_TIME_UNLIMITED: u16 = 0;
+pub const MAX_BYTES_PER_SECOND_UNLIMITED: u16 = 0;
+
+const fn align(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) & !(alignment - 1)
+}
+
+/// Size of a sequence of octets written at `offset`.
+const fn sample_data_size(offset: usize, data: &[u8]) -> usize {
+    align(offset, 4) + 4 + data.len()
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |      state     |                    (padding)                     |
+/// +----------------+----------------+----------------+----------------+
+/// |                          sequenceNumber                           |
+/// +----------------+----------------+----------------+----------------+
+/// |                         sessionTimeOffset                         |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SampleInfo {
+    pub state: u8,
+    pub sequence_number: u32,
+    pub session_time_offset: u32,
+}
+
+impl SampleInfo {
+    const fn size(offset: usize) -> usize {
+        align(offset + 1, 4) + 8
+    }
+
+    /// Info of a packed sample, whose `delta` is relative to this one.
+    pub fn with_delta(&self, delta: SampleInfoDelta) -> SampleInfo {
+        SampleInfo {
+            state: delta.state,
+            sequence_number: self
+                .sequence_number
+                .wrapping_add(delta.seq_number_delta as u32),
+            session_time_offset: self
+                .session_time_offset
+                .wrapping_add(delta.timestamp_delta as u32),
+        }
+    }
+}
+
+impl Serialize for SampleInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.state)?;
+        s.serialize_element(&self.sequence_number)?;
+        s.serialize_element(&self.session_time_offset)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SampleInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct SampleInfoVisitor;
+
+        impl<'de> Visitor<'de> for SampleInfoVisitor {
+            type Value = SampleInfo;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct SampleInfo")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(SampleInfo {
+                    state: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    sequence_number: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    session_time_offset: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 3, SampleInfoVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample<'a> {
+    pub info: SampleInfo,
+    pub data: &'a [u8],
+}
+
+impl Sample<'_> {
+    fn size(&self, offset: usize) -> usize {
+        sample_data_size(SampleInfo::size(offset), self.data)
+    }
+}
+
+impl Serialize for Sample<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.info)?;
+        s.serialize_element(self.data)?;
+        s.end()
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |      state     | seqNumberDelta |          timestampDelta         |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SampleInfoDelta {
+    pub state: u8,
+    pub seq_number_delta: u8,
+    pub timestamp_delta: u16,
+}
+
+impl Serialize for SampleInfoDelta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.state)?;
+        s.serialize_element(&self.seq_number_delta)?;
+        s.serialize_element(&self.timestamp_delta)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SampleInfoDelta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct SampleInfoDeltaVisitor;
+
+        impl<'de> Visitor<'de> for SampleInfoDeltaVisitor {
+            type Value = SampleInfoDelta;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct SampleInfoDelta")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(SampleInfoDelta {
+                    state: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    seq_number_delta: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    timestamp_delta: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 3, SampleInfoDeltaVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleDelta<'a> {
+    pub info_delta: SampleInfoDelta,
+    pub data: &'a [u8],
+}
+
+impl SampleDelta<'_> {
+    fn size(&self, offset: usize) -> usize {
+        sample_data_size(align(offset + 2, 2) + 2, self.data)
+    }
+}
+
+impl Serialize for SampleDelta<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.info_delta)?;
+        s.serialize_element(self.data)?;
+        s.end()
+    }
+}
+
+/// Samples sharing a base `info`, each one carrying only its difference from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PackedSamples<'a> {
+    pub info: SampleInfo,
+    pub sample_delta_seq: &'a [SampleDelta<'a>],
+}
+
+///
+/// Data written to or received from a DDS entity, in one of the formats of the
+/// WRITE_DATA and DATA submessages.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataRepresentation<'a> {
+    Data(&'a [u8]),
+    Sample(Sample<'a>),
+    DataSeq(&'a [&'a [u8]]),
+    SampleSeq(&'a [Sample<'a>]),
+    PackedSamples(PackedSamples<'a>),
+}
+
+impl DataRepresentation<'_> {
+    pub fn format(&self) -> DataFormat {
+        match *self {
+            DataRepresentation::Data(_) => DataFormat::FormatData,
+            DataRepresentation::Sample(_) => DataFormat::FormatSample,
+            DataRepresentation::DataSeq(_) => DataFormat::FormatDataSeq,
+            DataRepresentation::SampleSeq(_) => DataFormat::FormatSampleSeq,
+            DataRepresentation::PackedSamples(_) => DataFormat::FormatPackedSamples,
+        }
+    }
+
+    /// Serialized size when written at `offset`, including its alignment.
+    pub(crate) fn size(&self, offset: usize) -> usize {
+        match *self {
+            DataRepresentation::Data(data) => offset + data.len(),
+            DataRepresentation::Sample(sample) => sample.size(offset),
+            DataRepresentation::DataSeq(data_seq) => {
+                data_seq.iter().fold(align(offset, 4) + 4, |offset, data| {
+                    sample_data_size(offset, data)
+                })
+            }
+            DataRepresentation::SampleSeq(sample_seq) => sample_seq
+                .iter()
+                .fold(align(offset, 4) + 4, |offset, sample| sample.size(offset)),
+            DataRepresentation::PackedSamples(packed) => packed
+                .sample_delta_seq
+                .iter()
+                .fold(align(SampleInfo::size(offset), 4) + 4, |offset, delta| {
+                    delta.size(offset)
+                }),
+        }
+    }
+}
+
+impl Serialize for DataRepresentation<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        match *self {
+            // the data is not preceded by its length, it fills the submessage
+            DataRepresentation::Data(data) => {
+                for byte in data {
+                    s.serialize_element(byte)?;
+                }
+            }
+            DataRepresentation::Sample(sample) => s.serialize_element(&sample)?,
+            DataRepresentation::DataSeq(data_seq) => s.serialize_element(data_seq)?,
+            DataRepresentation::SampleSeq(sample_seq) => s.serialize_element(sample_seq)?,
+            DataRepresentation::PackedSamples(packed) => {
+                s.serialize_element(&packed.info)?;
+                s.serialize_element(packed.sample_delta_seq)?;
+            }
+        }
+        s.end()
+    }
+}
+
+///
+/// Limits how the agent delivers the samples of a READ_DATA request. The times
+/// are in milliseconds.
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |            maxSamples           |          maxElapsedTime         |
+/// +----------------+----------------+----------------+----------------+
+/// |         maxBytesPerSecond       |          minPacePeriod          |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeliveryControl {
+    pub max_samples: u16,
+    pub max_elapsed_time: u16,
+    pub max_bytes_per_second: u16,
+    pub min_pace_period: u16,
+}
+
+impl Default for DeliveryControl {
+    fn default() -> Self {
+        DeliveryControl {
+            max_samples: MAX_SAMPLES_UNLIMITED,
+            max_elapsed_time: MAX_ELAPSED_TIME_UNLIMITED,
+            max_bytes_per_second: MAX_BYTES_PER_SECOND_UNLIMITED,
+            min_pace_period: 0,
+        }
+    }
+}
+
+impl Serialize for DeliveryControl {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.max_samples)?;
+        s.serialize_element(&self.max_elapsed_time)?;
+        s.serialize_element(&self.max_bytes_per_second)?;
+        s.serialize_element(&self.min_pace_period)?;
+        s.end()
+    }
+}
+
+///
+/// What a data handler is told about a received sample besides its data: the
+/// request and the entity it was delivered for, the input stream it came from, and
+/// the identity of the request it belongs to when it was received by a requester
+/// or a replier.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataInfo {
+    pub object_id: ObjectId,
+    pub request_id: u16,
+    pub stream_id: StreamId,
+    pub format: DataFormat,
+    pub sample_info: Option<SampleInfo>,
+    pub sample_identity: Option<SampleIdentity>,
+}
+
+///
+/// Called once for every sample received for the entity it is registered for. The
+/// decoder is positioned at the beginning of the sample, which is aligned from there
+/// as it was serialized by the writer.
+///
+pub type DataHandler<'a> = &'a mut dyn FnMut(&DataInfo, &mut micro_cdr::Decoder);
@@ -0,0 +1,178 @@
+use serde::Deserialize;
+
+use crate::data::{DataHandler, DataInfo, SampleInfo, SampleInfoDelta};
+use crate::object_id::{ObjectId, ObjectKind};
+use crate::sample_identity::{SampleIdentity, SAMPLE_IDENTITY_SIZE};
+use crate::stream_id::StreamId;
+use crate::submessage::DataFormat;
+use crate::types::{BaseObjectRequest, BASE_OBJECT_REQUEST_SIZE};
+use crate::{error, micro_cdr, Endianness, MAX_DATA_HANDLERS};
+
+struct Registration<'a> {
+    object_id: ObjectId,
+    request_id: Option<u16>,
+    handler: DataHandler<'a>,
+}
+
+impl core::fmt::Debug for Registration<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Registration")
+            .field("object_id", &self.object_id)
+            .field("request_id", &self.request_id)
+            .finish_non_exhaustive()
+    }
+}
+
+///
+/// Handlers the samples of the DATA submessages are routed to, by the entity they
+/// were delivered for and optionally by the request that asked for them.
+///
+#[derive(Debug, Default)]
+pub(crate) struct DataHandlers<'a> {
+    registrations: [Option<Registration<'a>>; MAX_DATA_HANDLERS],
+}
+
+impl<'a> DataHandlers<'a> {
+    /// Registers `handler`, replacing the one with the same entity and request.
+    /// Returns false when there is no room left for it.
+    pub(crate) fn add(
+        &mut self,
+        object_id: ObjectId,
+        request_id: Option<u16>,
+        handler: DataHandler<'a>,
+    ) -> bool {
+        let index = match self.position(object_id, request_id) {
+            Some(index) => index,
+            None => match self.registrations.iter().position(Option::is_none) {
+                Some(index) => index,
+                None => return false,
+            },
+        };
+
+        self.registrations[index] = Some(Registration {
+            object_id,
+            request_id,
+            handler,
+        });
+        true
+    }
+
+    pub(crate) fn remove(&mut self, object_id: ObjectId, request_id: Option<u16>) -> bool {
+        match self.position(object_id, request_id) {
+            Some(index) => {
+                self.registrations[index] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, object_id: ObjectId, request_id: Option<u16>) -> Option<usize> {
+        self.registrations.iter().position(|registration| {
+            registration.as_ref().is_some_and(|registration| {
+                registration.object_id == object_id && registration.request_id == request_id
+            })
+        })
+    }
+
+    /// The handler registered for `request_id` is preferred over the one registered
+    /// for every request of the entity.
+    fn find(&mut self, object_id: ObjectId, request_id: u16) -> Option<&mut Registration<'a>> {
+        let index = self
+            .position(object_id, Some(request_id))
+            .or_else(|| self.position(object_id, None))?;
+        self.registrations[index].as_mut()
+    }
+
+    /// Calls the handler of the entity a DATA payload was delivered for with each
+    /// of the samples it carries, in the given `format`.
+    pub(crate) fn dispatch(
+        &mut self,
+        stream_id: StreamId,
+        format: DataFormat,
+        endianness: Endianness,
+        payload: &[u8],
+    ) -> error::Result<()> {
+        let mut ucdr = micro_cdr::Decoder::new_with_endianness(payload, endianness);
+        let base = BaseObjectRequest::deserialize(&mut ucdr)?;
+        let request_id = u16::from_be_bytes(base.request_id);
+
+        let registration = match self.find(base.object_id, request_id) {
+            Some(registration) => registration,
+            None => return Ok(()),
+        };
+
+        let mut info = DataInfo {
+            object_id: base.object_id,
+            request_id,
+            stream_id,
+            format,
+            sample_info: None,
+            sample_identity: None,
+        };
+
+        match format {
+            // the sample has no length, it fills the rest of the submessage
+            DataFormat::FormatData => {
+                let data = payload
+                    .get(BASE_OBJECT_REQUEST_SIZE..)
+                    .ok_or(error::Error::BufferNotEnough)?;
+                deliver(&mut *registration.handler, &mut info, endianness, data)?;
+            }
+            DataFormat::FormatSample => {
+                info.sample_info = Some(SampleInfo::deserialize(&mut ucdr)?);
+                let data = <&[u8]>::deserialize(&mut ucdr)?;
+                deliver(&mut *registration.handler, &mut info, endianness, data)?;
+            }
+            DataFormat::FormatDataSeq => {
+                let len = u32::deserialize(&mut ucdr)?;
+                for _ in 0..len {
+                    let data = <&[u8]>::deserialize(&mut ucdr)?;
+                    deliver(&mut *registration.handler, &mut info, endianness, data)?;
+                }
+            }
+            DataFormat::FormatSampleSeq => {
+                let len = u32::deserialize(&mut ucdr)?;
+                for _ in 0..len {
+                    info.sample_info = Some(SampleInfo::deserialize(&mut ucdr)?);
+                    let data = <&[u8]>::deserialize(&mut ucdr)?;
+                    deliver(&mut *registration.handler, &mut info, endianness, data)?;
+                }
+            }
+            DataFormat::FormatPackedSamples => {
+                let sample_info = SampleInfo::deserialize(&mut ucdr)?;
+                let len = u32::deserialize(&mut ucdr)?;
+                for _ in 0..len {
+                    let delta = SampleInfoDelta::deserialize(&mut ucdr)?;
+                    info.sample_info = Some(sample_info.with_delta(delta));
+                    let data = <&[u8]>::deserialize(&mut ucdr)?;
+                    deliver(&mut *registration.handler, &mut info, endianness, data)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Calls `handler` with a decoder positioned at the sample. The samples received by
+/// requesters and repliers begin with the identity of their request, which is
+/// removed from the data and given in `info`.
+fn deliver(
+    handler: &mut dyn FnMut(&DataInfo, &mut micro_cdr::Decoder),
+    info: &mut DataInfo,
+    endianness: Endianness,
+    mut data: &[u8],
+) -> error::Result<()> {
+    if info.object_id.kind == ObjectKind::Requester || info.object_id.kind == ObjectKind::Replier {
+        let mut ucdr = micro_cdr::Decoder::new_with_endianness(data, endianness);
+        info.sample_identity = Some(SampleIdentity::deserialize(&mut ucdr)?);
+        data = data
+            .get(SAMPLE_IDENTITY_SIZE..)
+            .ok_or(error::Error::BufferNotEnough)?;
+    }
+
+    let mut ucdr = micro_cdr::Decoder::new_with_endianness(data, endianness);
+    handler(info, &mut ucdr);
+    Ok(())
+}
@@ -14,6 +14,7 @@ pub enum Error {
     InvalidChar(char),
     InvalidCharEncoding,
     InvalidFormat(u8),
+    InvalidObjectKind(u8),
     InvalidUtf8Encoding(Utf8Error),
     InvalidString,
     SequenceMustHaveLength,
@@ -31,6 +32,7 @@ impl core::fmt::Display for Error {
             InvalidChar(v) => write!(f, "expected char of width 1. found {}", v),
             InvalidCharEncoding => write!(f, "char is not valid UTF-8"),
             InvalidFormat(v) => write!(f, "invalid format {v}"),
+            InvalidObjectKind(v) => write!(f, "invalid object kind {v}"),
             InvalidString => write!(f, "each character must have a length of 1"),
             InvalidUtf8Encoding(ref err) => core::fmt::Display::fmt(err, f),
             SequenceMustHaveLength => {
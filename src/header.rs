@@ -1,3 +1,4 @@
+use crate::seq_num::SeqNum;
 use crate::{error, micro_cdr};
 use serde::de;
 use serde::de::Visitor;
@@ -21,7 +22,7 @@ type ClientKey = [u8; 4];
 pub struct MessageHeader {
     pub session_id: u8,
     pub stream_id: u8,
-    pub sequence_num: u16,
+    pub sequence_num: SeqNum,
     pub key: Option<ClientKey>,
 }
 
@@ -33,7 +34,7 @@ impl Serialize for MessageHeader {
         let mut s = serializer.serialize_tuple(0)?;
         s.serialize_element(&self.session_id)?;
         s.serialize_element(&self.stream_id)?;
-        s.serialize_element(&self.sequence_num)?;
+        s.serialize_element(&self.sequence_num.get())?;
         if let Some(key) = self.key {
             s.serialize_element(&key[0])?;
             s.serialize_element(&key[1])?;
@@ -91,7 +92,7 @@ impl<'de> Deserialize<'de> for MessageHeader {
                 Ok(MessageHeader {
                     session_id,
                     stream_id,
-                    sequence_num: seq_num,
+                    sequence_num: seq_num.into(),
                     key,
                 })
             }
@@ -106,7 +107,7 @@ impl MessageHeader {
         MessageHeader {
             session_id,
             stream_id,
-            sequence_num: seq_num,
+            sequence_num: seq_num.into(),
             key,
         }
     }
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+
+use crate::object_id::ObjectKind;
+use crate::types::{BaseObjectRequest, ResultStatus};
+use crate::{error, micro_cdr, Endianness};
+
+pub use crate::types::Property;
+
+const ADDRESS_FORMAT_SMALL: u8 = 0x00;
+const ADDRESS_FORMAT_MEDIUM: u8 = 0x01;
+const ADDRESS_FORMAT_LARGE: u8 = 0x02;
+const ADDRESS_FORMAT_STRING: u8 = 0x03;
+
+///
+/// What the agent tells about itself in an INFO, the answer to a GET_INFO.
+///
+#[derive(Debug, Clone)]
+pub struct AgentInfo<'a> {
+    /// None when the agent did not send its configuration.
+    pub config: Option<AgentConfig<'a>>,
+    /// Greater than 0 when the agent is available, none when it did not send its
+    /// activity.
+    pub availability: Option<i16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentConfig<'a> {
+    pub xrce_version: [u8; 2],
+    pub xrce_vendor_id: [u8; 2],
+    pub properties: Properties<'a>,
+}
+
+///
+/// Properties of the agent, decoded from the INFO as they are iterated. The
+/// iteration stops at the first malformed one.
+///
+#[derive(Clone)]
+pub struct Properties<'a> {
+    ucdr: micro_cdr::Decoder<'a>,
+    remaining: u32,
+}
+
+impl core::fmt::Debug for Properties<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a> Iterator for Properties<'a> {
+    type Item = Property<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let property = <&str>::deserialize(&mut self.ucdr).and_then(|name| {
+            <&str>::deserialize(&mut self.ucdr).map(|value| Property { name, value })
+        });
+        match property {
+            Ok(property) => {
+                self.remaining -= 1;
+                Some(property)
+            }
+            Err(_) => {
+                self.remaining = 0;
+                None
+            }
+        }
+    }
+}
+
+///
+/// Decodes an INFO payload: the related request and its result, followed by the
+/// optional activity and the optional configuration of the object. Only those of
+/// an agent are decoded.
+///
+pub(crate) fn from_payload(payload: &[u8], endianness: Endianness) -> error::Result<AgentInfo<'_>> {
+    let mut ucdr = micro_cdr::Decoder::new_with_endianness(payload, endianness);
+    BaseObjectRequest::deserialize(&mut ucdr)?;
+    ResultStatus::deserialize(&mut ucdr)?;
+
+    let mut info = AgentInfo {
+        config: None,
+        availability: None,
+    };
+
+    if bool::deserialize(&mut ucdr)? {
+        // what follows the activity of other objects is not known
+        if u8::deserialize(&mut ucdr)? != ObjectKind::Agent as u8 {
+            return Ok(info);
+        }
+
+        info.availability = Some(i16::deserialize(&mut ucdr)?);
+        skip_locators(&mut ucdr)?;
+    }
+
+    if bool::deserialize(&mut ucdr)? && u8::deserialize(&mut ucdr)? == ObjectKind::Agent as u8 {
+        let _xrce_cookie = <[u8; 4]>::deserialize(&mut ucdr)?;
+        let xrce_version = <[u8; 2]>::deserialize(&mut ucdr)?;
+        let xrce_vendor_id = <[u8; 2]>::deserialize(&mut ucdr)?;
+
+        let remaining = if bool::deserialize(&mut ucdr)? {
+            u32::deserialize(&mut ucdr)?
+        } else {
+            0
+        };
+
+        info.config = Some(AgentConfig {
+            xrce_version,
+            xrce_vendor_id,
+            properties: Properties { ucdr, remaining },
+        });
+    }
+
+    Ok(info)
+}
+
+///
+/// Skips the addresses the agent can be reached at, which follow its availability.
+/// Each one is preceded by its format:
+///
+/// +--------+-----------------------------------+
+/// | SMALL  | address[2], port u8               |
+/// | MEDIUM | address[4], port u16              |
+/// | LARGE  | address[16], port u32             |
+/// | STRING | string                            |
+/// +--------+-----------------------------------+
+///
+fn skip_locators(ucdr: &mut micro_cdr::Decoder) -> error::Result<()> {
+    for _ in 0..u32::deserialize(&mut *ucdr)? {
+        match u8::deserialize(&mut *ucdr)? {
+            ADDRESS_FORMAT_SMALL => {
+                <[u8; 2]>::deserialize(&mut *ucdr)?;
+                u8::deserialize(&mut *ucdr)?;
+            }
+            ADDRESS_FORMAT_MEDIUM => {
+                <[u8; 4]>::deserialize(&mut *ucdr)?;
+                u16::deserialize(&mut *ucdr)?;
+            }
+            ADDRESS_FORMAT_LARGE => {
+                <[u8; 16]>::deserialize(&mut *ucdr)?;
+                u32::deserialize(&mut *ucdr)?;
+            }
+            ADDRESS_FORMAT_STRING => {
+                <&str>::deserialize(&mut *ucdr)?;
+            }
+            format => return Err(error::Error::InvalidFormat(format)),
+        }
+    }
+    Ok(())
+}
+
+///
+/// Called with the INFO received from the agent, in reply to [`Session::ping_agent`].
+///
+/// [`Session::ping_agent`]: crate::session::Session::ping_agent
+///
+pub type InfoHandler<'a> = &'a mut dyn FnMut(&AgentInfo);
+
+/// Where the session keeps the [`InfoHandler`], printed without it.
+#[derive(Default)]
+pub(crate) struct InfoHandlerSlot<'a>(pub(crate) Option<InfoHandler<'a>>);
+
+impl core::fmt::Debug for InfoHandlerSlot<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("InfoHandlerSlot")
+            .field(&self.0.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,29 @@
+use crate::seq_num::SeqNum;
+
+#[derive(Debug)]
+pub(crate) struct InputBestEffortStream {
+    last_handled: SeqNum,
+}
+
+impl InputBestEffortStream {
+    pub(crate) fn new() -> Self {
+        InputBestEffortStream {
+            last_handled: SeqNum::MAX,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.last_handled = SeqNum::MAX;
+    }
+
+    /// Returns true when the message is newer than the last one handled, older
+    /// or repeated messages must be discarded.
+    pub(crate) fn receive_message(&mut self, seq_num: SeqNum) -> bool {
+        if self.last_handled < seq_num {
+            self.last_handled = seq_num;
+            true
+        } else {
+            false
+        }
+    }
+}
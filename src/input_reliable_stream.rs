@@ -0,0 +1,192 @@
+use crate::seq_num::SeqNum;
+
+/// Each history slot begins with the length of the message it stores, 0 when empty.
+const INTERNAL_RELIABLE_BUFFER_OFFSET: usize = core::mem::size_of::<usize>();
+
+///
+/// The buffer is split into `history` slots where the messages received out of
+/// order are kept until the ones preceding them arrive. The FRAGMENT submessages
+/// are joined in the optional reassembly buffer.
+///
+#[derive(Debug)]
+pub(crate) struct InputReliableStream<'storage> {
+    buffer: &'storage mut [u8],
+    history: u16,
+    last_handled: SeqNum,
+    last_announced: SeqNum,
+    reassembly: Option<&'storage mut [u8]>,
+    reassembly_len: usize,
+    /// Set when a fragment was lost or did not fit, until the last one is received.
+    reassembly_dropped: bool,
+}
+
+impl<'storage> InputReliableStream<'storage> {
+    pub(crate) fn new(buffer: &'storage mut [u8], history: u16) -> Self {
+        let mut stream = InputReliableStream {
+            buffer,
+            history,
+            last_handled: SeqNum::MAX,
+            last_announced: SeqNum::MAX,
+            reassembly: None,
+            reassembly_len: 0,
+            reassembly_dropped: false,
+        };
+        stream.reset();
+        stream
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for index in 0..self.history {
+            self.set_slot_length(SeqNum::new(index), 0);
+        }
+
+        self.last_handled = SeqNum::MAX;
+        self.last_announced = SeqNum::MAX;
+        self.reassembly_len = 0;
+        self.reassembly_dropped = false;
+    }
+
+    pub(crate) fn set_reassembly_buffer(&mut self, buffer: &'storage mut [u8]) {
+        self.reassembly = Some(buffer);
+    }
+
+    /// Appends the payload of a FRAGMENT. Once the `last` one is received, the
+    /// reassembly buffer is taken out of the stream along with the length of the
+    /// submessages it holds, and must be given back once they are read.
+    pub(crate) fn push_fragment(
+        &mut self,
+        payload: &[u8],
+        last: bool,
+    ) -> Option<(&'storage mut [u8], usize)> {
+        let buffer = self.reassembly.as_deref_mut()?;
+
+        let begin = self.reassembly_len;
+        match buffer.get_mut(begin..begin + payload.len()) {
+            Some(fragment) if !self.reassembly_dropped => {
+                fragment.copy_from_slice(payload);
+                self.reassembly_len += payload.len();
+            }
+            _ => self.reassembly_dropped = true,
+        }
+
+        if !last {
+            return None;
+        }
+
+        let len = self.reassembly_len;
+        let dropped = self.reassembly_dropped;
+        self.reassembly_len = 0;
+        self.reassembly_dropped = false;
+        if dropped {
+            None
+        } else {
+            self.reassembly.take().map(|buffer| (buffer, len))
+        }
+    }
+
+    fn slot_size(&self) -> usize {
+        self.buffer.len() / self.history as usize
+    }
+
+    fn slot_begin(&self, seq_num: SeqNum) -> usize {
+        (seq_num.get() % self.history) as usize * self.slot_size()
+    }
+
+    fn slot_length(&self, seq_num: SeqNum) -> usize {
+        let begin = self.slot_begin(seq_num);
+        let mut raw = [0u8; INTERNAL_RELIABLE_BUFFER_OFFSET];
+        raw.copy_from_slice(&self.buffer[begin..begin + INTERNAL_RELIABLE_BUFFER_OFFSET]);
+        usize::from_ne_bytes(raw)
+    }
+
+    fn set_slot_length(&mut self, seq_num: SeqNum, length: usize) {
+        let begin = self.slot_begin(seq_num);
+        self.buffer[begin..begin + INTERNAL_RELIABLE_BUFFER_OFFSET]
+            .copy_from_slice(&length.to_ne_bytes());
+    }
+
+    /// Returns true when the message is the next one expected and can be read right away.
+    /// Messages ahead of it are stored if they fit in the history, duplicates are dropped.
+    pub(crate) fn receive_message(&mut self, seq_num: SeqNum, buf: &[u8]) -> bool {
+        let window_end = self.last_handled.add(self.history);
+        if !(self.last_handled < seq_num && seq_num <= window_end) {
+            return false;
+        }
+
+        if self.last_announced < seq_num {
+            self.last_announced = seq_num;
+        }
+
+        if seq_num == self.last_handled.add(1) {
+            // a copy stored before a heartbeat skipped up to it is no longer needed
+            self.set_slot_length(seq_num, 0);
+            self.last_handled = seq_num;
+            return true;
+        }
+
+        if self.slot_length(seq_num) == 0
+            && buf.len() + INTERNAL_RELIABLE_BUFFER_OFFSET <= self.slot_size()
+        {
+            let begin = self.slot_begin(seq_num) + INTERNAL_RELIABLE_BUFFER_OFFSET;
+            self.buffer[begin..begin + buf.len()].copy_from_slice(buf);
+            self.set_slot_length(seq_num, buf.len());
+        }
+        false
+    }
+
+    /// Copies into `buf` the stored message following the last handled one, if it
+    /// has been received, and marks it as handled.
+    pub(crate) fn next_message_available(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let seq_num = self.last_handled.add(1);
+        let len = self.slot_length(seq_num);
+        if len == 0 || len > buf.len() {
+            return None;
+        }
+
+        let begin = self.slot_begin(seq_num) + INTERNAL_RELIABLE_BUFFER_OFFSET;
+        buf[..len].copy_from_slice(&self.buffer[begin..begin + len]);
+        self.set_slot_length(seq_num, 0);
+        self.last_handled = seq_num;
+        Some(len)
+    }
+
+    pub(crate) fn process_heartbeat(&mut self, first_seq_num: SeqNum, last_seq_num: SeqNum) {
+        // the agent no longer keeps the messages before `first_seq_num`
+        if self.last_handled.add(1) < first_seq_num {
+            let last_lost = first_seq_num.sub(1);
+            let skipped = self.last_handled.distance(last_lost).min(self.history);
+            for i in 1..=skipped {
+                self.set_slot_length(self.last_handled.add(i), 0);
+            }
+            self.last_handled = last_lost;
+
+            // the lost messages may hold fragments of the message being joined, which
+            // is dropped up to its last fragment
+            if self.reassembly_len > 0 {
+                self.reassembly_dropped = true;
+            }
+        }
+
+        if self.last_announced < self.last_handled {
+            self.last_announced = self.last_handled;
+        }
+        if self.last_announced < last_seq_num {
+            self.last_announced = last_seq_num;
+        }
+    }
+
+    /// Returns the first sequence number not received yet and the bitmap of the
+    /// announced messages that are missing after it.
+    pub(crate) fn compute_acknack(&self) -> (SeqNum, u16) {
+        let first_unacked = self.last_handled.add(1);
+        let buffers_to_check = self.last_handled.distance(self.last_announced).min(16);
+
+        let mut nack_bitmap = 0u16;
+        for i in 0..buffers_to_check {
+            if self.slot_length(first_unacked.add(i)) == 0 {
+                nack_bitmap |= 1 << i;
+            }
+        }
+        (first_unacked, nack_bitmap)
+    }
+}
@@ -1,24 +1,47 @@
 #![cfg_attr(all(not(test), not(feature = "std")), no_std)]
 #![no_main]
 
+pub mod data;
+mod data_handler;
 pub mod error;
 pub mod micro_cdr;
 pub mod session;
 
 mod header;
-mod stream_id;
-mod submessage;
+pub mod info;
+mod input_best_effort_stream;
+mod input_reliable_stream;
+pub mod object_id;
+mod output_best_effort_stream;
+mod output_reliable_stream;
+pub mod representation;
+pub mod sample_identity;
+mod seq_num;
+pub mod status;
+pub mod stream_id;
+mod stream_storage;
+pub mod submessage;
+pub mod supervision;
 mod types;
 
-mod communication;
+pub mod communication;
 pub mod serial;
 
 pub mod time;
 
 const MIN_SESSION_CONNECTION_INTERVAL: i64 = 1000;
 const MAX_SESSION_CONNECTION_ATTEMPTS: usize = 10;
+const MAX_TRANSPORT_MTU: usize = 512;
+const MAX_OUTPUT_BEST_EFFORT_STREAMS: usize = 1;
+const MAX_OUTPUT_RELIABLE_STREAMS: usize = 1;
+const MAX_INPUT_BEST_EFFORT_STREAMS: usize = 1;
+const MAX_INPUT_RELIABLE_STREAMS: usize = 1;
+const MIN_HEARTBEAT_TIME_INTERVAL: i64 = 100;
+const MAX_DATA_HANDLERS: usize = 4;
+const MAX_STATUS_REQUESTS: usize = 16;
+const MAX_SUPERVISED_ENTITIES: usize = 8;
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Endianness {
     BigEndianness,
     LittleEndianness,
@@ -34,6 +57,12 @@ pub enum Error {
     Deined,
     InvalidData,
     Incompatible,
+    Rejected(u8),
+    OutOfResources,
+    BufferNotEnough,
+    SampleTooLong,
+    InvalidStream,
+    InvalidUsage,
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -51,14 +80,113 @@ fn panic() -> ! {
 #[defmt_test::tests]
 mod test {
     use defmt_rtt as _;
+    use serde::{Deserialize, Serialize};
     use stm32f1xx_hal as _;
 
     use crate::{
+        communication::{Receiver, Transmitter},
+        data::{DataInfo, DataRepresentation, DeliveryControl, Sample, SampleInfo},
+        data_handler::DataHandlers,
         header::{self, MessageHeader},
-        micro_cdr, session, submessage,
-        types::{CLIENT_Representation, CREATE_CLIENT_Payload},
+        info,
+        input_best_effort_stream::InputBestEffortStream,
+        input_reliable_stream::InputReliableStream,
+        micro_cdr,
+        object_id::{ObjectId, ObjectKind, ObjectPrefix},
+        output_reliable_stream::OutputReliableStream,
+        representation::{self, ObjectRepresentation},
+        sample_identity::SampleIdentity,
+        seq_num::SeqNum,
+        session,
+        status::{self, RequestStatus, Status, StatusTable},
+        stream_id::{StreamDirection, StreamId, StreamType},
+        submessage,
+        supervision::Supervisor,
+        time::Clock,
+        types::{
+            ACKNACK_Payload, BaseObjectRequest, CLIENT_Representation, CREATE_CLIENT_Payload,
+            CREATE_Payload, DELETE_Payload, GET_INFO_Payload, HEARTBEAT_Payload, ObjectVariant,
+            READ_DATA_Payload, STATUS_AGENT_Payload, STATUS_Payload, TIMESTAMP_Payload,
+            TIMESTAMP_REPLY_Payload, Time, WRITE_DATA_Payload, INFO_ACTIVITY, INFO_CONFIGURATION,
+            OBJECTID_AGENT, OBJECTID_CLIENT, REQUEST_GET_INFO, REQUEST_LOGOUT,
+        },
+        Error, MAX_STATUS_REQUESTS, MIN_HEARTBEAT_TIME_INTERVAL,
     };
 
+    const MOCK_MESSAGE_SIZE: usize = 128;
+    const MOCK_MESSAGES: usize = 8;
+
+    /// Transport keeping the messages sent and giving back the queued ones in order.
+    /// Its clock moves on by the whole timeout when there is nothing left to receive.
+    struct MockTransport {
+        sent: [[u8; MOCK_MESSAGE_SIZE]; MOCK_MESSAGES],
+        sent_len: [usize; MOCK_MESSAGES],
+        sent_count: usize,
+        inbox: [[u8; MOCK_MESSAGE_SIZE]; MOCK_MESSAGES],
+        inbox_len: [usize; MOCK_MESSAGES],
+        inbox_count: usize,
+        received: usize,
+        now: i32,
+        /// Milliseconds the clock moves on by for each message received.
+        latency: i32,
+    }
+
+    impl MockTransport {
+        fn new() -> Self {
+            MockTransport {
+                sent: [[0; MOCK_MESSAGE_SIZE]; MOCK_MESSAGES],
+                sent_len: [0; MOCK_MESSAGES],
+                sent_count: 0,
+                inbox: [[0; MOCK_MESSAGE_SIZE]; MOCK_MESSAGES],
+                inbox_len: [0; MOCK_MESSAGES],
+                inbox_count: 0,
+                received: 0,
+                now: 0,
+                latency: 0,
+            }
+        }
+
+        fn sent(&self, index: usize) -> &[u8] {
+            &self.sent[index][..self.sent_len[index]]
+        }
+
+        fn queue(&mut self, msg: &[u8]) {
+            self.inbox[self.inbox_count][..msg.len()].copy_from_slice(msg);
+            self.inbox_len[self.inbox_count] = msg.len();
+            self.inbox_count += 1;
+        }
+    }
+
+    impl Transmitter for MockTransport {
+        type Ok = ();
+
+        fn send_msg(&mut self, buf: &[u8]) -> crate::Result<()> {
+            self.sent[self.sent_count][..buf.len()].copy_from_slice(buf);
+            self.sent_len[self.sent_count] = buf.len();
+            self.sent_count += 1;
+            Ok(())
+        }
+    }
+
+    impl Receiver for MockTransport {
+        fn receive_msg(&mut self, timeout: i32) -> crate::Result<&[u8]> {
+            if self.received == self.inbox_count {
+                self.now = self.now.wrapping_add(timeout);
+                return Err(Error::Timeout);
+            }
+
+            self.now = self.now.wrapping_add(self.latency);
+            self.received += 1;
+            Ok(&self.inbox[self.received - 1][..self.inbox_len[self.received - 1]])
+        }
+    }
+
+    impl Clock for MockTransport {
+        fn now(&mut self) -> i32 {
+            self.now
+        }
+    }
+
     #[test]
     fn ser_de_create_client() {
         let mut create_session_buffer = [0u8; 28];
@@ -125,6 +253,1230 @@ mod test {
         }
     }
 
+    #[test]
+    fn de_status_agent() {
+        let status_agent_buffer = [
+            // Submessage Header
+            0x04, 0x01, 0x0B, 0x00, // Payload
+            0x00, 0x00, b'X', b'R', b'C', b'E', 0x01, 0x00, 0x0F, 0x0F, 0x00,
+        ];
+
+        let submessage_header =
+            submessage::SubMessageHeader::from_slice(&status_agent_buffer).unwrap();
+        assert_eq!(
+            submessage::SubMessageHeader::StatusAgent(11),
+            submessage_header
+        );
+
+        let mut ucdr = micro_cdr::Decoder::new(&status_agent_buffer[4..]);
+        let payload = STATUS_AGENT_Payload::deserialize(&mut ucdr).unwrap();
+        assert_eq!(payload.result.status, 0x00);
+        assert_eq!(payload.agent_info.xrce_cookie, [b'X', b'R', b'C', b'E']);
+        assert_eq!(payload.agent_info.xrce_version, [0x01, 0x00]);
+        assert_eq!(payload.agent_info.xrce_vendor_id, [0x0F, 0x0F]);
+    }
+
+    #[test]
+    fn create_session_across_clock_wrap() {
+        let mut transport = MockTransport::new();
+        transport.now = i32::MAX;
+        transport.latency = 1;
+        transport.queue(&[
+            0x81, 0x00, 0x00, 0x00, // STATUS_AGENT
+            0x04, 0x01, 0x0B, 0x00, 0x00, 0x00, b'X', b'R', b'C', b'E', 0x01, 0x00, 0x0F, 0x0F,
+            0x00,
+        ]);
+
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        assert_eq!(None, session.agent_vendor_id());
+        session.create().unwrap();
+        assert_eq!(Some([0x0F, 0x0F]), session.agent_vendor_id());
+    }
+
+    #[test]
+    fn ser_delete_de_status() {
+        let mut delete_buffer = [0u8; 8];
+        let len = DELETE_Payload(BaseObjectRequest {
+            request_id: REQUEST_LOGOUT,
+            object_id: OBJECTID_CLIENT,
+        })
+        .to_slice(&mut delete_buffer)
+        .unwrap();
+        assert_eq!(len, 8);
+        assert_eq!(
+            [0x03, 0x01, 0x04, 0x00, 0x00, 0x02, 0xFF, 0xFE],
+            delete_buffer
+        );
+
+        let status_buffer = [0x00, 0x02, 0xFF, 0xFE, 0x00, 0x00];
+        let mut ucdr = micro_cdr::Decoder::new(&status_buffer);
+        let payload = STATUS_Payload::deserialize(&mut ucdr).unwrap();
+        assert_eq!(payload.related_request.request_id, REQUEST_LOGOUT);
+        assert_eq!(payload.related_request.object_id, OBJECTID_CLIENT);
+        assert_eq!(payload.result.status, 0x00);
+    }
+
+    #[test]
+    fn seq_num_arithmetic() {
+        let max = SeqNum::MAX;
+        assert_eq!(max.add(1), SeqNum::new(0));
+        assert_eq!(SeqNum::new(0).sub(1), max);
+        assert_eq!(max.distance(SeqNum::new(4)), 5);
+
+        assert!(max < SeqNum::new(0));
+        assert!(SeqNum::new(0x7FFF) > SeqNum::new(0));
+        assert!(SeqNum::new(0x8001) < SeqNum::new(0));
+        assert_eq!(SeqNum::new(0x8000).partial_cmp(&SeqNum::new(0)), None);
+    }
+
+    #[test]
+    fn submessage_list() {
+        let buf = [
+            // Submessage Header
+            0x0B, 0x01, 0x01, 0x00, // Payload
+            0xAA, 0x00, 0x00, 0x00, // Submessage Header
+            0x0A, 0x01, 0x02, 0x00, // Payload
+            0xBB, 0xCC,
+        ];
+
+        let mut submessages = submessage::SubMessages::new(&buf);
+        let submessage = submessages.next().unwrap().unwrap();
+        assert_eq!(
+            submessage.header,
+            submessage::SubMessageHeader::HeartBeat(1)
+        );
+        assert_eq!(submessage.flags, 0x01);
+        assert_eq!(submessage.endianness(), crate::Endianness::LittleEndianness);
+        assert_eq!(submessage.payload, [0xAA]);
+        let submessage = submessages.next().unwrap().unwrap();
+        assert_eq!(submessage.header, submessage::SubMessageHeader::AckNack(2));
+        assert_eq!(submessage.payload, [0xBB, 0xCC]);
+        assert!(submessages.next().is_none());
+
+        let malformed = [0x0A, 0x01, 0x08, 0x00, 0xBB, 0xCC];
+        let mut submessages = submessage::SubMessages::new(&malformed);
+        assert!(submessages.next().unwrap().is_err());
+        assert!(submessages.next().is_none());
+
+        let unknown = [0x1F, 0x01, 0x00, 0x00];
+        assert!(submessage::SubMessages::new(&unknown)
+            .next()
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn de_big_endian_heartbeat() {
+        let buf = [
+            // Submessage Header
+            0x0B, 0x00, 0x05, 0x00, // Payload
+            0x00, 0x05, 0x00, 0x07, 0x80,
+        ];
+
+        let submessage = submessage::SubMessages::new(&buf).next().unwrap().unwrap();
+        let endianness = submessage.endianness();
+        assert_eq!(endianness, crate::Endianness::BigEndianness);
+
+        let mut ucdr = micro_cdr::Decoder::new_with_endianness(submessage.payload, endianness);
+        let heartbeat = HEARTBEAT_Payload::deserialize(&mut ucdr).unwrap();
+        assert_eq!(heartbeat.first_unacked_seq_nr, 5);
+        assert_eq!(heartbeat.last_unacked_seq_nr, 7);
+        assert_eq!(heartbeat.stream_id, 0x80);
+    }
+
+    #[test]
+    fn object_id_raw() {
+        let object_id = ObjectId::new(0x123, ObjectKind::DataWriter);
+        assert_eq!(object_id.to_raw(), [0x12, 0x35]);
+        assert_eq!(ObjectId::from_raw([0x12, 0x35]).unwrap(), object_id);
+        assert_eq!(OBJECTID_CLIENT.to_raw(), [0xFF, 0xFE]);
+        assert!(ObjectId::from_raw([0x00, 0x19]).is_err());
+
+        // the kinds outside of the entities are still read
+        assert_eq!(
+            ObjectId::new(0x001, ObjectKind::Invalid),
+            ObjectId::from_raw([0x00, 0x10]).unwrap()
+        );
+        assert_eq!(
+            ObjectId::new(0x001, ObjectKind::Other),
+            ObjectId::from_raw([0x00, 0x1F]).unwrap()
+        );
+
+        let prefix = object_id.prefix();
+        assert_eq!(ObjectPrefix::new(0x123), prefix);
+        assert_eq!(object_id, prefix.object_id(ObjectKind::DataWriter));
+        let mut buf = [0u8; 2];
+        prefix
+            .serialize(&mut micro_cdr::Encoder::new(&mut buf))
+            .unwrap();
+        assert_eq!([0x12, 0x30], buf);
+        assert_eq!(
+            prefix,
+            ObjectPrefix::deserialize(&mut micro_cdr::Decoder::new(&[0x12, 0x35])).unwrap()
+        );
+    }
+
+    #[test]
+    fn ser_create_participant() {
+        let payload = CREATE_Payload {
+            base: BaseObjectRequest {
+                request_id: [0x00, 0x03],
+                object_id: ObjectId::new(0x001, ObjectKind::Participant),
+            },
+            object_representation: ObjectVariant::Participant {
+                representation: ObjectRepresentation::Ref("part"),
+                domain_id: 0x0102,
+            },
+        };
+
+        let mut create_buffer = [0u8; 32];
+        let len = payload.to_slice(&mut create_buffer).unwrap();
+        assert_eq!(len, payload.size());
+        assert_eq!(
+            [
+                0x00, 0x03, 0x00, 0x11, 0x01, 0x01, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, b'p', b'a',
+                b'r', b't', 0x00, 0x00, 0x02, 0x01
+            ],
+            create_buffer[..len]
+        );
+    }
+
+    #[test]
+    fn ser_create_entities() {
+        let participant_id = ObjectId::new(0x001, ObjectKind::Participant);
+        let publisher_id = ObjectId::new(0x001, ObjectKind::Publisher);
+        let subscriber_id = ObjectId::new(0x001, ObjectKind::Subscriber);
+        let entities: [(ObjectKind, ObjectVariant, &[u8]); 5] = [
+            (
+                ObjectKind::Topic,
+                ObjectVariant::Topic {
+                    representation: ObjectRepresentation::Ref("topic"),
+                    participant_id,
+                },
+                &[
+                    0x02, 0x01, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, b't', b'o', b'p', b'i', b'c',
+                    0x00, 0x00, 0x11,
+                ],
+            ),
+            (
+                ObjectKind::Publisher,
+                ObjectVariant::Publisher {
+                    representation: ObjectRepresentation::Xml("<p/>"),
+                    participant_id,
+                },
+                &[
+                    0x03, 0x02, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, b'<', b'p', b'/', b'>', 0x00,
+                    0x00, 0x11,
+                ],
+            ),
+            (
+                ObjectKind::DataWriter,
+                ObjectVariant::DataWriter {
+                    representation: ObjectRepresentation::Binary(&[0xAA, 0xBB]),
+                    publisher_id,
+                },
+                &[
+                    0x05, 0x03, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0xAA, 0xBB, 0x00, 0x13,
+                ],
+            ),
+            (
+                ObjectKind::Subscriber,
+                ObjectVariant::Subscriber {
+                    representation: ObjectRepresentation::Xml("<s/>"),
+                    participant_id,
+                },
+                &[
+                    0x04, 0x02, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, b'<', b's', b'/', b'>', 0x00,
+                    0x00, 0x11,
+                ],
+            ),
+            (
+                ObjectKind::DataReader,
+                ObjectVariant::DataReader {
+                    representation: ObjectRepresentation::Ref("reader"),
+                    subscriber_id,
+                },
+                &[
+                    0x06, 0x01, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, b'r', b'e', b'a', b'd', b'e',
+                    b'r', 0x00, 0x00, 0x14,
+                ],
+            ),
+        ];
+
+        for (kind, object_representation, expected) in entities {
+            let payload = CREATE_Payload {
+                base: BaseObjectRequest {
+                    request_id: [0x00, 0x04],
+                    object_id: ObjectId::new(0x002, kind),
+                },
+                object_representation,
+            };
+
+            let mut create_buffer = [0u8; 32];
+            let len = payload.to_slice(&mut create_buffer).unwrap();
+            assert_eq!(len, payload.size());
+            assert_eq!([0x00, 0x04, 0x00, 0x20 | kind as u8], create_buffer[..4]);
+            assert_eq!(expected, &create_buffer[4..len]);
+        }
+    }
+
+    #[test]
+    fn create_by_reference() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 4 * 64];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        let participant_id = ObjectId::new(0x001, ObjectKind::Participant);
+        let mode = representation::CreationMode::default();
+
+        // only the XML and binary representations of a publisher or subscriber exist
+        let representation = ObjectRepresentation::Ref("profile");
+        let publisher_id = ObjectId::new(0x001, ObjectKind::Publisher);
+        assert!(matches!(
+            session.create_publisher(stream, publisher_id, participant_id, representation, mode),
+            Err(Error::InvalidUsage)
+        ));
+        let subscriber_id = ObjectId::new(0x001, ObjectKind::Subscriber);
+        assert!(matches!(
+            session.create_subscriber(stream, subscriber_id, participant_id, representation, mode),
+            Err(Error::InvalidUsage)
+        ));
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        assert_eq!(0, transport.sent_count);
+    }
+
+    #[test]
+    fn ser_de_sample_identity() {
+        let sample_identity = SampleIdentity {
+            guid_prefix: [0x0A; 12],
+            entity_key: [0x01, 0x02, 0x03],
+            entity_kind: 0x04,
+            sequence_number_high: 0,
+            sequence_number_low: 0x0005,
+        };
+
+        let mut reply_buffer = [0u8; 28];
+        let len = {
+            let mut ucdr = micro_cdr::Encoder::new(&mut reply_buffer);
+            sample_identity.serialize(&mut ucdr).unwrap();
+            ucdr.finalize()
+        };
+        assert_eq!(len, 24);
+        reply_buffer[24] = 0x09;
+
+        let (identity, data) = SampleIdentity::from_slice(&reply_buffer[..25]).unwrap();
+        assert_eq!(identity, sample_identity);
+        assert_eq!(identity.request_id(), 5);
+        assert_eq!(data, [0x09]);
+    }
+
+    #[test]
+    fn ser_write_data_sample_seq() {
+        let info = SampleInfo {
+            state: 0x01,
+            sequence_number: 0x02,
+            session_time_offset: 0x03,
+        };
+        let payload = WRITE_DATA_Payload {
+            base: BaseObjectRequest {
+                request_id: [0x00, 0x06],
+                object_id: ObjectId::new(0x001, ObjectKind::DataWriter),
+            },
+            data: DataRepresentation::SampleSeq(&[
+                Sample {
+                    info,
+                    data: &[0x07],
+                },
+                Sample {
+                    info,
+                    data: &[0x08, 0x09],
+                },
+            ]),
+        };
+        assert_eq!(
+            payload.data.format(),
+            submessage::DataFormat::FormatSampleSeq
+        );
+
+        let mut write_data_buffer = [0u8; 64];
+        let len = payload.to_slice(&mut write_data_buffer).unwrap();
+        assert_eq!(len, payload.size());
+        assert_eq!(len, 42);
+        assert_eq!(
+            [0x00, 0x06, 0x00, 0x15, 0x02, 0x00, 0x00, 0x00],
+            write_data_buffer[..8]
+        );
+        assert_eq!(
+            [0x02, 0x00, 0x00, 0x00, 0x08, 0x09],
+            write_data_buffer[36..42]
+        );
+    }
+
+    #[test]
+    fn ser_read_data() {
+        let payload = READ_DATA_Payload {
+            base: BaseObjectRequest {
+                request_id: [0x00, 0x04],
+                object_id: ObjectId::new(0x001, ObjectKind::DataReader),
+            },
+            preferred_stream_id: 0x80,
+            data_format: submessage::DataFormat::FormatData,
+            delivery_control: Some(DeliveryControl {
+                max_samples: 5,
+                max_elapsed_time: 6,
+                max_bytes_per_second: 7,
+                min_pace_period: 8,
+            }),
+        };
+
+        let mut read_data_buffer = [0u8; 16];
+        let len = payload.to_slice(&mut read_data_buffer).unwrap();
+        assert_eq!(len, payload.size());
+        assert_eq!(
+            [
+                0x00, 0x04, 0x00, 0x16, 0x80, 0x00, 0x00, 0x01, 0x05, 0x00, 0x06, 0x00, 0x07, 0x00,
+                0x08, 0x00
+            ],
+            read_data_buffer
+        );
+
+        // the format is written as in the flags of the DATA submessages
+        let payload = READ_DATA_Payload {
+            data_format: submessage::DataFormat::FormatSampleSeq,
+            delivery_control: None,
+            ..payload
+        };
+        let len = payload.to_slice(&mut read_data_buffer).unwrap();
+        assert_eq!(len, payload.size());
+        assert_eq!(0x0A, read_data_buffer[5]);
+    }
+
+    #[test]
+    fn dispatch_data_seq() {
+        let replier = ObjectId::new(0x002, ObjectKind::Replier);
+        // DATA of a replier in big endian, two samples preceded by their identity
+        let mut payload = [0u8; 4 + 4 + 32 + (4 + 24 + 2)];
+        payload[..4].copy_from_slice(&[0x00, 0x07, 0x00, 0x28]);
+        payload[7] = 2;
+        for (i, sample) in payload[8..].chunks_mut(32).enumerate() {
+            sample[3] = 26;
+            sample[27] = 1 + i as u8;
+            sample[29] = 0x10 + i as u8;
+        }
+
+        let mut received = [(0u32, 0u16); 2];
+        let mut count = 0;
+        let mut handler = |info: &DataInfo, ucdr: &mut micro_cdr::Decoder| {
+            assert_eq!(info.object_id, replier);
+            assert_eq!(info.request_id, 7);
+            received[count] = (
+                info.sample_identity.unwrap().sequence_number_low,
+                u16::deserialize(ucdr).unwrap(),
+            );
+            count += 1;
+        };
+
+        let mut handlers = DataHandlers::default();
+        assert!(handlers.add(replier, None, &mut handler));
+        handlers
+            .dispatch(
+                StreamId::from_raw(0x80, StreamDirection::InputStream),
+                submessage::DataFormat::FormatDataSeq,
+                crate::Endianness::BigEndianness,
+                &payload,
+            )
+            .unwrap();
+        assert_eq!([(1, 0x0010), (2, 0x0011)], received);
+    }
+
+    #[test]
+    fn status_table() {
+        let mut table = StatusTable::default();
+        for request_id in 0..=MAX_STATUS_REQUESTS as u16 {
+            table.track(request_id);
+        }
+        // the oldest request was forgotten to make room for the last one
+        assert_eq!(None, table.get(0));
+        assert_eq!(Some(RequestStatus::Pending), table.get(1));
+
+        let status = Status {
+            object_id: ObjectId::new(0x001, ObjectKind::Topic),
+            request_id: MAX_STATUS_REQUESTS as u16,
+            status: status::STATUS_ERR_UNKNOWN_REFERENCE,
+            implementation_status: 0,
+        };
+        assert!(table.record(status));
+        assert!(!table.record(Status {
+            request_id: 0,
+            ..status
+        }));
+        assert_eq!(
+            Some(RequestStatus::Received(status)),
+            table.get(MAX_STATUS_REQUESTS as u16)
+        );
+        assert!(!status.is_ok());
+    }
+
+    #[test]
+    fn flush_best_effort() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 512];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_best_effort_stream(&mut buffer)
+            .unwrap();
+        let datawriter = ObjectId::new(0x001, ObjectKind::DataWriter);
+
+        // the buffer is longer than the MTU of 256 bytes, which bounds the message
+        assert!(matches!(
+            session.write_data(stream, datawriter, DataRepresentation::Data(&[0; 245])),
+            Err(Error::BufferNotEnough)
+        ));
+        session
+            .write_data(stream, datawriter, DataRepresentation::Data(&[1; 2]))
+            .unwrap();
+        session
+            .write_data(stream, datawriter, DataRepresentation::Data(&[2; 2]))
+            .unwrap();
+        session.flush_output_streams().unwrap();
+        // nothing is left to send
+        session.flush_output_streams().unwrap();
+        session
+            .write_data(stream, datawriter, DataRepresentation::Data(&[3; 2]))
+            .unwrap();
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        assert_eq!(2, transport.sent_count);
+        let message = transport.sent(0);
+        assert_eq!([0x81, 0x01, 0, 0], message[..4]);
+        assert_eq!(4 + 12 + 10, message.len());
+        assert_eq!([1, 1], message[12..14]);
+        assert_eq!([2, 2], message[24..]);
+        let message = transport.sent(1);
+        assert_eq!([0x81, 0x01, 1, 0], message[..4]);
+        assert_eq!([3, 3], message[12..]);
+    }
+
+    #[test]
+    fn input_best_effort_order() {
+        let mut stream = InputBestEffortStream::new();
+        assert!(stream.receive_message(SeqNum::new(0)));
+        assert!(!stream.receive_message(SeqNum::new(0)));
+        assert!(stream.receive_message(SeqNum::new(3)));
+        assert!(!stream.receive_message(SeqNum::new(2)));
+
+        // newer through the wraparound, as long as less than half of the range ahead
+        assert!(stream.receive_message(SeqNum::new(0x7FFF)));
+        assert!(stream.receive_message(SeqNum::new(0xFFFE)));
+        assert!(stream.receive_message(SeqNum::new(0x0001)));
+        assert!(!stream.receive_message(SeqNum::new(0xFFFF)));
+    }
+
+    #[test]
+    fn read_best_effort_data() {
+        let datareader = ObjectId::new(0x001, ObjectKind::DataReader);
+        let mut transport = MockTransport::new();
+        for (seq_num, value) in [(1u8, 1u8), (1, 2), (0, 3), (3, 4)] {
+            let mut message = [0x81, 0x01, seq_num, 0, 0, 0, 0, 0, 0, 0, 0, 0, value];
+            submessage::SubMessageHeader::Data(5, submessage::DataFormat::FormatData)
+                .to_slice(&mut message[4..8])
+                .unwrap();
+            message[10..12].copy_from_slice(&datareader.to_raw());
+            transport.queue(&message);
+        }
+
+        let mut received = [0u8; 4];
+        let mut count = 0;
+        let mut handler = |_: &DataInfo, ucdr: &mut micro_cdr::Decoder| {
+            received[count] = u8::deserialize(ucdr).unwrap();
+            count += 1;
+        };
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        session.create_input_best_effort_stream().unwrap();
+        session
+            .set_data_handler(datareader, None, &mut handler)
+            .unwrap();
+        session.run_until_timeout(10).unwrap();
+        drop(session);
+
+        // the repeated and older messages are discarded
+        assert_eq!([1, 4], received[..count]);
+    }
+
+    #[test]
+    fn read_reliable_data_after_heartbeat() {
+        let datareader = ObjectId::new(0x001, ObjectKind::DataReader);
+        let mut transport = MockTransport::new();
+        // the messages 1 and 3 are lost
+        for (seq_num, value) in [(0u8, 1u8), (2, 3), (4, 5)] {
+            let mut message = [0x81, 0x80, seq_num, 0, 0, 0, 0, 0, 0, 0, 0, 0, value];
+            submessage::SubMessageHeader::Data(5, submessage::DataFormat::FormatData)
+                .to_slice(&mut message[4..8])
+                .unwrap();
+            message[10..12].copy_from_slice(&datareader.to_raw());
+            transport.queue(&message);
+        }
+        // the agent no longer keeps the messages before 4
+        transport.queue(&[
+            0x81, 0x00, 0x00, 0x00, 0x0B, 0x00, 0x05, 0x00, 0x00, 0x04, 0x00, 0x04, 0x80,
+        ]);
+
+        let mut received = [0u8; 4];
+        let mut count = 0;
+        let mut handler = |_: &DataInfo, ucdr: &mut micro_cdr::Decoder| {
+            received[count] = u8::deserialize(ucdr).unwrap();
+            count += 1;
+        };
+        let mut buffer = [0u8; 4 * 32];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        session
+            .create_input_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        session
+            .set_data_handler(datareader, None, &mut handler)
+            .unwrap();
+        session.run_until_timeout(10).unwrap();
+        drop(session);
+
+        // the message stored after the skipped ones is read right away
+        assert_eq!([1, 5], received[..count]);
+        assert_eq!(4, transport.sent_count);
+        let message = transport.sent(3);
+        let acknack =
+            ACKNACK_Payload::deserialize(&mut micro_cdr::Decoder::new(&message[8..])).unwrap();
+        assert_eq!(5, acknack.first_unacked_seq_num);
+        assert_eq!([0, 0], acknack.nack_bitmap);
+        assert_eq!(0x80, acknack.stream_id);
+    }
+
+    #[test]
+    fn run_until_heartbeats() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 4 * 64];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        let participant_id = ObjectId::new(0x001, ObjectKind::Participant);
+        session
+            .create_participant(
+                stream,
+                participant_id,
+                0,
+                ObjectRepresentation::Ref("part"),
+                representation::CreationMode::default(),
+            )
+            .unwrap();
+
+        // the wait is cut to send a HEARTBEAT after 100 ms, not the whole timeout longer
+        assert!(!session.run_until_confirm_delivery(250).unwrap());
+        drop(session);
+
+        assert_eq!(250, transport.now);
+        assert_eq!(2, transport.sent_count);
+        assert_eq!(
+            submessage::SubMessageHeader::HeartBeat(5),
+            submessage::SubMessageHeader::from_slice(&transport.sent(1)[4..8]).unwrap()
+        );
+    }
+
+    #[test]
+    fn run_until_status() {
+        let request_id = u16::from_be_bytes(REQUEST_LOGOUT) + 1;
+        let participant_id = ObjectId::new(0x001, ObjectKind::Participant);
+
+        let mut transport = MockTransport::new();
+        let mut message = [
+            0x81,
+            0x00,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            status::STATUS_OK,
+            0,
+        ];
+        submessage::SubMessageHeader::Status(6)
+            .to_slice(&mut message[4..8])
+            .unwrap();
+        message[8..10].copy_from_slice(&request_id.to_be_bytes());
+        message[10..12].copy_from_slice(&participant_id.to_raw());
+        transport.queue(&message);
+
+        let mut message = [0x81, 0x00, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        ACKNACK_Payload {
+            first_unacked_seq_num: 1,
+            nack_bitmap: [0, 0],
+            stream_id: 0x80,
+        }
+        .to_slice(&mut message[4..])
+        .unwrap();
+        transport.queue(&message);
+
+        let mut buffer = [0u8; 4 * 64];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        let mode = representation::CreationMode::default();
+        let representation = ObjectRepresentation::Ref("part");
+        assert_eq!(
+            request_id,
+            session
+                .create_participant(stream, participant_id, 0, representation, mode)
+                .unwrap()
+        );
+
+        // both return as soon as they are done, before the clock moved on
+        assert!(session.run_until_all_status(1_000, &[request_id]).unwrap());
+        assert!(session.run_until_confirm_delivery(1_000).unwrap());
+        assert_eq!(0, session.epoch_nanos());
+
+        // the time spent waiting is the timeout, without any HEARTBEAT to send
+        assert!(!session
+            .run_until_all_status(300, &[request_id, request_id + 1])
+            .unwrap());
+        drop(session);
+
+        assert_eq!(300, transport.now);
+        assert_eq!(1, transport.sent_count);
+    }
+
+    #[test]
+    fn output_reliable_up_to_date() {
+        let mut buffer = [0u8; 128];
+        let mut stream = OutputReliableStream::new(&mut buffer, 2, 4);
+        assert!(stream.is_up_to_date());
+
+        stream.prepare_buffer(8).unwrap();
+        stream.prepare_next_to_send().unwrap();
+        assert!(!stream.is_up_to_date());
+        assert_eq!(i64::MAX, stream.next_heartbeat_timestamp());
+        assert!(!stream.update_heartbeat_timestamp(0));
+        assert_eq!(
+            MIN_HEARTBEAT_TIME_INTERVAL,
+            stream.next_heartbeat_timestamp()
+        );
+
+        stream.process_acknack(0, SeqNum::new(1));
+        assert!(stream.is_up_to_date());
+        assert_eq!(i64::MAX, stream.next_heartbeat_timestamp());
+    }
+
+    #[test]
+    fn output_reliable_acknack() {
+        let mut buffer = [0u8; 4 * 32];
+        let mut stream = OutputReliableStream::new(&mut buffer, 4, 4);
+        for _ in 0..3 {
+            stream.prepare_new_message(4).unwrap();
+            stream.prepare_next_to_send().unwrap();
+        }
+
+        // an ACKNACK for messages never sent is ignored
+        assert_eq!(0, stream.process_acknack(0b1111, SeqNum::new(4)));
+        assert_eq!(SeqNum::new(0), stream.first_unacked_seq_num());
+
+        // the messages reported lost which were not sent yet are left out
+        assert_eq!(0b0010, stream.process_acknack(0b0110, SeqNum::new(1)));
+        assert_eq!(SeqNum::new(1), stream.first_unacked_seq_num());
+        assert!(!stream.is_up_to_date());
+
+        // an older ACKNACK does not move the acknowledged messages back
+        assert_eq!(0, stream.process_acknack(0b0001, SeqNum::new(0)));
+        assert_eq!(SeqNum::new(1), stream.first_unacked_seq_num());
+
+        assert_eq!(0, stream.process_acknack(0, SeqNum::new(3)));
+        assert!(stream.is_up_to_date());
+    }
+
+    #[test]
+    fn input_reliable_order() {
+        let mut buffer = [0u8; 4 * 32];
+        let mut stream = InputReliableStream::new(&mut buffer, 4);
+        let mut message = [0u8; 16];
+
+        assert!(stream.receive_message(SeqNum::new(0), &[1]));
+        assert!(!stream.receive_message(SeqNum::new(0), &[1]));
+
+        // kept until the one before it arrives, the first copy is the one kept
+        assert!(!stream.receive_message(SeqNum::new(2), &[3]));
+        assert!(!stream.receive_message(SeqNum::new(2), &[4]));
+        assert_eq!(None, stream.next_message_available(&mut message));
+        assert_eq!((SeqNum::new(1), 0b01), stream.compute_acknack());
+
+        // beyond the history, neither kept nor announced
+        assert!(!stream.receive_message(SeqNum::new(5), &[6]));
+        assert_eq!((SeqNum::new(1), 0b01), stream.compute_acknack());
+
+        assert!(stream.receive_message(SeqNum::new(1), &[2]));
+        assert_eq!(Some(1), stream.next_message_available(&mut message));
+        assert_eq!(3, message[0]);
+        assert_eq!(None, stream.next_message_available(&mut message));
+        assert_eq!((SeqNum::new(3), 0), stream.compute_acknack());
+
+        // a stored message resent once it is the next one is not read again in its slot
+        assert!(!stream.receive_message(SeqNum::new(4), &[5]));
+        stream.process_heartbeat(SeqNum::new(4), SeqNum::new(4));
+        assert!(stream.receive_message(SeqNum::new(4), &[5]));
+        for seq_num in 5..8 {
+            assert!(stream.receive_message(SeqNum::new(seq_num), &[seq_num as u8 + 1]));
+        }
+        assert_eq!(None, stream.next_message_available(&mut message));
+        assert_eq!((SeqNum::new(8), 0), stream.compute_acknack());
+    }
+
+    #[test]
+    fn read_stream_acknack() {
+        // a reliable message whose STATUS is longer than the message itself
+        let mut message = [0x81, 0x80, 0, 0, 0, 0, 0, 0, 0, 0];
+        submessage::SubMessageHeader::Status(20)
+            .to_slice(&mut message[4..8])
+            .unwrap();
+        let mut transport = MockTransport::new();
+        transport.queue(&message);
+
+        let mut buffer = [0u8; 4 * 32];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        session
+            .create_input_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        assert!(session.run_until_timeout(10).unwrap());
+        drop(session);
+
+        // it is acknowledged all the same
+        assert_eq!(1, transport.sent_count);
+        let message = transport.sent(0);
+        assert_eq!(
+            submessage::SubMessageHeader::AckNack(5),
+            submessage::SubMessageHeader::from_slice(&message[4..8]).unwrap()
+        );
+        let acknack =
+            ACKNACK_Payload::deserialize(&mut micro_cdr::Decoder::new(&message[8..])).unwrap();
+        assert_eq!(1, acknack.first_unacked_seq_num);
+        assert_eq!([0, 0], acknack.nack_bitmap);
+        assert_eq!(0x80, acknack.stream_id);
+    }
+
+    #[test]
+    fn output_reliable_new_message() {
+        let mut buffer = [0u8; 4 * 32];
+        let mut stream = OutputReliableStream::new(&mut buffer, 4, 4);
+        assert_eq!(
+            32 - core::mem::size_of::<usize>() - 4,
+            stream.max_message_len()
+        );
+        assert!(stream.has_free_slots(4));
+        assert!(!stream.has_free_slots(5));
+
+        // a submessage that would fit after the pending one still opens a new slot
+        stream.prepare_buffer(4).unwrap();
+        stream.prepare_new_message(4).unwrap();
+        assert!(stream.has_free_slots(2));
+        assert!(!stream.has_free_slots(3));
+
+        let (message, _) = stream.prepare_next_to_send().unwrap();
+        assert_eq!(8, message.len());
+    }
+
+    #[test]
+    fn copy_parts() {
+        let parts: [&[u8]; 3] = [&[1, 2], &[3, 4, 5], &[6]];
+        let mut buf = [0u8; 3];
+        session::copy_parts(&mut buf, &parts, 1);
+        assert_eq!([2, 3, 4], buf);
+
+        let mut buf = [0u8; 4];
+        session::copy_parts(&mut buf, &parts, 4);
+        assert_eq!([5, 6, 0, 0], buf);
+    }
+
+    #[test]
+    fn write_fragmented_data() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 4 * 48];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        let datawriter = ObjectId::new(0x001, ObjectKind::DataWriter);
+
+        // 36 bytes per message, which a serialized sample can't exceed
+        let mut data = [0u8; 60];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        assert!(matches!(
+            session.write(stream, datawriter, &data[..]),
+            Err(Error::SampleTooLong)
+        ));
+
+        let request_id = session
+            .write_data(stream, datawriter, DataRepresentation::Data(&data))
+            .unwrap();
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        // the subheader, the base and the data of the WRITE_DATA split in 32 bytes
+        let mut submessage = [0u8; 4 + 4 + 60];
+        submessage::SubMessageHeader::WriteData(64, submessage::DataFormat::FormatData)
+            .to_slice(&mut submessage[..4])
+            .unwrap();
+        BaseObjectRequest {
+            request_id: request_id.to_be_bytes(),
+            object_id: datawriter,
+        }
+        .serialize(&mut micro_cdr::Encoder::new(&mut submessage[4..8]))
+        .unwrap();
+        submessage[8..].copy_from_slice(&data);
+
+        assert_eq!(3, transport.sent_count);
+        for (index, expected) in submessage.chunks(32).enumerate() {
+            let message = transport.sent(index);
+            assert_eq!([0x81, 0x80, index as u8, 0], message[..4]);
+            let header = submessage::SubMessageHeader::from_slice(&message[4..8]).unwrap();
+            assert_eq!(
+                submessage::SubMessageHeader::Fragment(expected.len() as u16, index == 2),
+                header
+            );
+            assert_eq!(expected, &message[8..]);
+        }
+    }
+
+    #[test]
+    fn write_sample() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 2 * 64];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 2)
+            .unwrap();
+        let datawriter = ObjectId::new(0x001, ObjectKind::DataWriter);
+
+        // a sample longer than a whole message releases the slot opened for it
+        assert!(matches!(
+            session.write(stream, datawriter, &[0u8; 60][..]),
+            Err(Error::SampleTooLong)
+        ));
+
+        // each sample opens a new slot while there is one free, then is appended
+        session.write(stream, datawriter, &0x1234u16).unwrap();
+        session.write(stream, datawriter, &0x5678u16).unwrap();
+        session.write(stream, datawriter, &0x9ABCu16).unwrap();
+
+        // a sample longer than the room left in the pending message is truncated
+        assert!(matches!(
+            session.write(stream, datawriter, &[0u8; 20][..]),
+            Err(Error::BufferNotEnough)
+        ));
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        assert_eq!(2, transport.sent_count);
+        let message = transport.sent(0);
+        assert_eq!([0x81, 0x80, 0, 0], message[..4]);
+        assert_eq!(4 + 4 + 4 + 2, message.len());
+        assert_eq!(0x1234u16.to_ne_bytes(), message[12..]);
+
+        let message = transport.sent(1);
+        assert_eq!([0x81, 0x80, 1, 0], message[..4]);
+        assert_eq!(4 + 12 + 4 + 4 + 2, message.len());
+        assert_eq!(0x5678u16.to_ne_bytes(), message[12..14]);
+        assert_eq!(0x9ABCu16.to_ne_bytes(), message[24..]);
+    }
+
+    #[test]
+    fn write_sample_mtu() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 512];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 1)
+            .unwrap();
+        let datawriter = ObjectId::new(0x001, ObjectKind::DataWriter);
+
+        // the slot could hold it but not a message of the default MTU of 256 bytes
+        assert!(matches!(
+            session.write(stream, datawriter, &[0u8; 241][..]),
+            Err(Error::SampleTooLong)
+        ));
+        session.write(stream, datawriter, &[0u8; 240][..]).unwrap();
+    }
+
+    #[test]
+    fn write_fragmented_history_full() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 2 * 48];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 2)
+            .unwrap();
+        let datawriter = ObjectId::new(0x001, ObjectKind::DataWriter);
+
+        // three fragments don't fit in a history of two, nothing is written
+        assert!(matches!(
+            session.write_data(stream, datawriter, DataRepresentation::Data(&[0; 60])),
+            Err(Error::BufferNotEnough)
+        ));
+        session
+            .write_data(stream, datawriter, DataRepresentation::Data(&[0; 20]))
+            .unwrap();
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        assert_eq!(1, transport.sent_count);
+        assert_eq!(4 + 4 + 4 + 20, transport.sent(0).len());
+    }
+
+    #[test]
+    fn input_reliable_reassembly() {
+        let mut buffer = [0u8; 64];
+        let mut reassembly = [0u8; 8];
+        let mut stream = InputReliableStream::new(&mut buffer, 2);
+        assert!(stream.push_fragment(&[1, 2], true).is_none());
+
+        stream.set_reassembly_buffer(&mut reassembly);
+        assert!(stream.push_fragment(&[1, 2, 3], false).is_none());
+        let (message, len) = stream.push_fragment(&[4, 5], true).unwrap();
+        assert_eq!([1, 2, 3, 4, 5], message[..len]);
+        stream.set_reassembly_buffer(message);
+
+        // a message longer than the buffer is dropped up to its last fragment
+        assert!(stream.push_fragment(&[0; 6], false).is_none());
+        assert!(stream.push_fragment(&[0; 6], true).is_none());
+        let (message, len) = stream.push_fragment(&[6], true).unwrap();
+        assert_eq!([6], message[..len]);
+        stream.set_reassembly_buffer(message);
+
+        // messages skipped between two fragmented ones don't drop the next one
+        stream.process_heartbeat(SeqNum::new(2), SeqNum::new(3));
+        assert!(stream.push_fragment(&[7, 8], false).is_none());
+        let (message, len) = stream.push_fragment(&[9], true).unwrap();
+        assert_eq!([7, 8, 9], message[..len]);
+        stream.set_reassembly_buffer(message);
+
+        // the message being joined may have fragments among the skipped messages
+        assert!(stream.push_fragment(&[10], false).is_none());
+        stream.process_heartbeat(SeqNum::new(4), SeqNum::new(5));
+        assert!(stream.push_fragment(&[11], true).is_none());
+        let (message, len) = stream.push_fragment(&[12], true).unwrap();
+        assert_eq!([12], message[..len]);
+    }
+
+    #[test]
+    fn sync_time() {
+        let mut transport = MockTransport::new();
+        transport.now = 1_000;
+        transport.latency = 4;
+        // t0 is the time of the TIMESTAMP, the first reply answers an older one
+        for (t0, t1, t2) in [
+            (500_000_000i64, 10_500_000_000, 10_500_000_000),
+            (1_000_000_000, 11_001_000_000, 11_002_000_000),
+        ] {
+            let mut reply = [0u8; 4 + 4 + 24];
+            reply[..4].copy_from_slice(&[0x81, 0x00, 0, 0]);
+            let mut ucdr = micro_cdr::Encoder::new(&mut reply[4..]);
+            submessage::SubMessageHeader::TimeStampReply(24)
+                .serialize(&mut ucdr)
+                .unwrap();
+            for nanos in [t2, t1, t0] {
+                Time::from_nanos(nanos).serialize(&mut ucdr).unwrap();
+            }
+            transport.queue(&reply);
+        }
+
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        assert!(session.sync_time(100).unwrap());
+
+        // t3 is 1.008 s, after both replies, so the offset is -9.9975 s
+        assert_eq!(7_000_000, session.round_trip_nanos());
+        assert_eq!(1_008_000_000 + 9_997_500_000, session.epoch_nanos());
+        drop(session);
+
+        assert_eq!(1, transport.sent_count);
+    }
+
+    #[test]
+    fn ser_de_timestamp() {
+        let time = Time::from_nanos(3_000_000_042);
+        assert_eq!(
+            Time {
+                seconds: 3,
+                nanoseconds: 42
+            },
+            time
+        );
+        assert_eq!(3_000_000_042, time.to_nanos());
+
+        let mut timestamp_buffer = [0u8; 12];
+        let len = TIMESTAMP_Payload {
+            transmit_timestamp: time,
+        }
+        .to_slice(&mut timestamp_buffer)
+        .unwrap();
+        assert_eq!(12, len);
+        assert_eq!(
+            [0x0E, 0x01, 0x08, 0x00, 0x03, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00],
+            timestamp_buffer
+        );
+
+        let reply = [
+            0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00,
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03,
+        ];
+        let mut ucdr =
+            micro_cdr::Decoder::new_with_endianness(&reply, crate::Endianness::BigEndianness);
+        let reply = TIMESTAMP_REPLY_Payload::deserialize(&mut ucdr).unwrap();
+        assert_eq!(5_000_000_001, reply.transmit_timestamp.to_nanos());
+        assert_eq!(4_000_000_002, reply.receive_timestamp.to_nanos());
+        assert_eq!(3_000_000_003, reply.originate_timestamp.to_nanos());
+    }
+
+    #[test]
+    fn ser_de_info() {
+        let mut get_info_buffer = [0u8; 12];
+        let len = GET_INFO_Payload {
+            base: BaseObjectRequest {
+                request_id: REQUEST_GET_INFO,
+                object_id: OBJECTID_AGENT,
+            },
+            info_mask: INFO_CONFIGURATION | INFO_ACTIVITY,
+        }
+        .to_slice(&mut get_info_buffer)
+        .unwrap();
+        assert_eq!(12, len);
+        assert_eq!(
+            [0x02, 0x01, 0x08, 0x00, 0x00, 0x09, 0xFF, 0xFD, 0x03, 0x00, 0x00, 0x00],
+            get_info_buffer
+        );
+
+        // activity: availability 1 and a medium locator, then config
+        let info_buffer = [
+            0x00, 0x09, 0xFF, 0xFD, 0x00, 0x00, 0x01, 0x0D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x01, 0xC0, 0xA8, 0x01, 0x02, 0x00, 0xDE, 0x1F, 0x01, 0x0D, b'X', b'R',
+            b'C', b'E', 0x01, 0x00, 0x01, 0x0F, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x00, b'a', 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, b'b', 0x00,
+        ];
+        let agent_info =
+            info::from_payload(&info_buffer, crate::Endianness::LittleEndianness).unwrap();
+        assert_eq!(Some(1), agent_info.availability);
+        let config = agent_info.config.unwrap();
+        assert_eq!([0x01, 0x00], config.xrce_version);
+        assert_eq!([0x01, 0x0F], config.xrce_vendor_id);
+        let mut properties = config.properties;
+        let property = properties.next().unwrap();
+        assert_eq!(("a", "b"), (property.name, property.value));
+        assert!(properties.next().is_none());
+    }
+
+    #[test]
+    fn supervised_creation() {
+        let mut transport = MockTransport::new();
+        let mut buffer = [0u8; 4 * 64];
+        let mut supervision_buffer = [0u8; 24];
+        let mut session = session::Session::new([0x11, 0x22, 0x33, 0x44], &mut transport);
+        let stream = session
+            .create_output_reliable_stream(&mut buffer, 4)
+            .unwrap();
+        let mode = representation::CreationMode::default();
+        let representation = ObjectRepresentation::Ref("part");
+
+        assert!(matches!(session.supervise(10), Err(Error::InvalidUsage)));
+        session
+            .enable_supervision(&mut supervision_buffer, 2)
+            .unwrap();
+
+        let participant_id = ObjectId::new(1, ObjectKind::Participant);
+        session
+            .create_participant(stream, participant_id, 0, representation, mode)
+            .unwrap();
+        session.flush_output_streams().unwrap();
+
+        // no room left to keep it, so it is not written either
+        let participant_id = ObjectId::new(2, ObjectKind::Participant);
+        assert!(matches!(
+            session.create_participant(stream, participant_id, 0, representation, mode),
+            Err(Error::OutOfResources)
+        ));
+        session.flush_output_streams().unwrap();
+        drop(session);
+
+        assert_eq!(1, transport.sent_count);
+        assert_eq!(4 + 4 + 20, transport.sent(0).len());
+    }
+
+    #[test]
+    fn supervisor_creations() {
+        let stream_id = StreamId::new(0, StreamType::ReliableStream, StreamDirection::OutputStream);
+        let participant_id = ObjectId::new(1, ObjectKind::Participant);
+        let topic_id = ObjectId::new(1, ObjectKind::Topic);
+        let publisher_id = ObjectId::new(1, ObjectKind::Publisher);
+        let mode = representation::CreationMode::default();
+
+        let mut buffer = [0u8; 8];
+        let mut supervisor = Supervisor::default();
+        assert!(supervisor
+            .record(stream_id, participant_id, None, mode, 2)
+            .is_none());
+
+        supervisor.enable(&mut buffer, 2);
+        let mut record = |object_id, parent_id, payload: &[u8]| {
+            supervisor
+                .record(stream_id, object_id, parent_id, mode, payload.len())
+                .map(|buf| buf.copy_from_slice(payload))
+                .is_some()
+        };
+        assert!(record(participant_id, None, &[1, 1]));
+        assert!(record(topic_id, Some(participant_id), &[2, 2, 2]));
+        assert!(record(publisher_id, Some(participant_id), &[3]));
+        assert!(!record(
+            ObjectId::new(2, ObjectKind::Participant),
+            None,
+            &[4, 4, 4]
+        ));
+        // replaced in place, before its children
+        assert!(record(participant_id, None, &[5, 5, 5, 5]));
+
+        {
+            let mut creations = supervisor.creations();
+            let (creation, payload) = creations.next().unwrap();
+            assert_eq!(
+                (participant_id, &[5u8, 5, 5, 5][..]),
+                (creation.object_id, payload)
+            );
+            let (creation, payload) = creations.next().unwrap();
+            assert_eq!((topic_id, &[2u8, 2, 2][..]), (creation.object_id, payload));
+            let (creation, payload) = creations.next().unwrap();
+            assert_eq!((publisher_id, &[3u8][..]), (creation.object_id, payload));
+            assert!(creations.next().is_none());
+        }
+
+        supervisor.remove(topic_id);
+        assert_eq!(2, supervisor.creations().count());
+        supervisor.remove(participant_id);
+        assert_eq!(0, supervisor.creations().count());
+
+        assert!(!supervisor.ping_answered());
+        assert_eq!(1, supervisor.ping_failed());
+        assert!(!supervisor.ping_answered());
+        supervisor.ping_failed();
+        assert_eq!(2, supervisor.ping_failed());
+        assert!(supervisor.ping_answered());
+        supervisor.reestablished();
+        supervisor.agent_reset();
+        assert!(supervisor.ping_answered());
+    }
+
     #[test]
     fn ser_de_submessageheader() {
         let mut submessage_header_buf = [0u8; 256];
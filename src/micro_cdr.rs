@@ -185,12 +185,13 @@ impl serde::Serializer for &mut Encoder<'_> {
         if !v.is_ascii() {
             Err(Error::InvalidString)
         } else {
+            // strings are written with their null terminator
             let l = v.len() + 1;
             self.write_usize_as_u32(l)?;
             self.check_avaliable(l)?;
             unsafe {
-                let data_ptr = v.as_ptr();
-                ptr::copy_nonoverlapping(data_ptr, self.pos, l);
+                ptr::copy_nonoverlapping(v.as_ptr(), self.pos, v.len());
+                *self.pos.add(v.len()) = 0;
                 self.pos = self.pos.add(l);
             }
             self.offset += l;
@@ -462,6 +463,7 @@ impl serde::ser::SerializeStructVariant for &mut Encoder<'_> {
     }
 }
 
+#[derive(Clone)]
 pub struct Decoder<'storage> {
     pos: *const u8,
     end: *const u8,
@@ -483,8 +485,8 @@ impl<'storage> Decoder<'storage> {
         }
     }
 
-    pub fn new_with_endianness(buffer: &'storage mut [u8], endianness: Endianness) -> Self {
-        let ptr = buffer.as_mut_ptr();
+    pub fn new_with_endianness(buffer: &'storage [u8], endianness: Endianness) -> Self {
+        let ptr = buffer.as_ptr();
         Decoder {
             // buf: buffer,
             pos: ptr,
@@ -524,8 +526,11 @@ impl<'storage> Decoder<'storage> {
     fn read_str(&mut self) -> error::Result<&'storage str> {
         core::str::from_utf8({
             let v = self.read_bytes()?;
-            let len = v.len();
-            &v[..(len - 1)]
+            // the length counts the terminating null character, so it is never 0
+            match v.split_last() {
+                Some((_, v)) => v,
+                None => return Err(Error::InvalidString),
+            }
         })
         .map_err(|e| Error::InvalidUtf8Encoding(e))
     }
@@ -536,6 +541,7 @@ impl<'storage> Decoder<'storage> {
         unsafe {
             let sli = core::slice::from_raw_parts(self.pos, len as usize);
             self.pos = self.pos.add(len as usize);
+            self.offset += len as usize;
             Ok(sli)
         }
     }
@@ -0,0 +1,203 @@
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Serialize,
+};
+
+const OBJECT_ID_MAX: u16 = 0x0FFF;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectKind {
+    /// No object, as in the STATUS of a request that could not be decoded.
+    Invalid = 0x00,
+    Participant = 0x01,
+    Topic = 0x02,
+    Publisher = 0x03,
+    Subscriber = 0x04,
+    DataWriter = 0x05,
+    DataReader = 0x06,
+    Requester = 0x07,
+    Replier = 0x08,
+    Type = 0x0A,
+    QosProfile = 0x0B,
+    Application = 0x0C,
+    Agent = 0x0D,
+    Client = 0x0E,
+    /// An object of a kind defined outside of the specification.
+    Other = 0x0F,
+}
+
+impl TryFrom<u8> for ObjectKind {
+    type Error = crate::error::Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(Self::Invalid),
+            0x01 => Ok(Self::Participant),
+            0x02 => Ok(Self::Topic),
+            0x03 => Ok(Self::Publisher),
+            0x04 => Ok(Self::Subscriber),
+            0x05 => Ok(Self::DataWriter),
+            0x06 => Ok(Self::DataReader),
+            0x07 => Ok(Self::Requester),
+            0x08 => Ok(Self::Replier),
+            0x0A => Ok(Self::Type),
+            0x0B => Ok(Self::QosProfile),
+            0x0C => Ok(Self::Application),
+            0x0D => Ok(Self::Agent),
+            0x0E => Ok(Self::Client),
+            0x0F => Ok(Self::Other),
+            _ => Err(crate::error::Error::InvalidObjectKind(value)),
+        }
+    }
+}
+
+///
+/// 0                8       12      16
+/// +----------------+-------+-------+
+/// |        id (12 bits)    |  kind |
+/// +----------------+-------+-------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectId {
+    pub id: u16,
+    pub kind: ObjectKind,
+}
+
+impl ObjectId {
+    /// Only the 12 lower bits of `id` are kept.
+    pub const fn new(id: u16, kind: ObjectKind) -> Self {
+        ObjectId {
+            id: id & OBJECT_ID_MAX,
+            kind,
+        }
+    }
+
+    pub const fn to_raw(self) -> [u8; 2] {
+        [
+            (self.id >> 4) as u8,
+            ((self.id << 4) as u8) | self.kind as u8,
+        ]
+    }
+
+    pub const fn prefix(self) -> ObjectPrefix {
+        ObjectPrefix { id: self.id }
+    }
+
+    pub fn from_raw(raw: [u8; 2]) -> crate::error::Result<Self> {
+        Ok(ObjectId {
+            id: ((raw[0] as u16) << 4) | (raw[1] >> 4) as u16,
+            kind: ObjectKind::try_from(raw[1] & 0x0F)?,
+        })
+    }
+}
+
+///
+/// The id of an object without its kind, which the objects of different kinds created
+/// together share.
+///
+/// 0                8       12      16
+/// +----------------+-------+-------+
+/// |        id (12 bits)    |   0   |
+/// +----------------+-------+-------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectPrefix {
+    pub id: u16,
+}
+
+impl ObjectPrefix {
+    /// Only the 12 lower bits of `id` are kept.
+    pub const fn new(id: u16) -> Self {
+        ObjectPrefix {
+            id: id & OBJECT_ID_MAX,
+        }
+    }
+
+    pub const fn object_id(self, kind: ObjectKind) -> ObjectId {
+        ObjectId::new(self.id, kind)
+    }
+
+    pub const fn to_raw(self) -> [u8; 2] {
+        [(self.id >> 4) as u8, (self.id << 4) as u8]
+    }
+
+    /// The 4 lower bits, where the kind of an object id is, are ignored.
+    pub const fn from_raw(raw: [u8; 2]) -> Self {
+        ObjectPrefix {
+            id: ((raw[0] as u16) << 4) | (raw[1] >> 4) as u16,
+        }
+    }
+}
+
+impl Serialize for ObjectPrefix {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let raw = self.to_raw();
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&raw[0])?;
+        s.serialize_element(&raw[1])?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectPrefix {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = <[u8; 2]>::deserialize(deserializer)?;
+        Ok(ObjectPrefix::from_raw(raw))
+    }
+}
+
+impl Serialize for ObjectId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let raw = self.to_raw();
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&raw[0])?;
+        s.serialize_element(&raw[1])?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ObjectIdVisitor;
+
+        impl<'de> Visitor<'de> for ObjectIdVisitor {
+            type Value = ObjectId;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct ObjectId")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let raw: [u8; 2] = [
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                ];
+                ObjectId::from_raw(raw).map_err(|_| {
+                    de::Error::invalid_value(
+                        de::Unexpected::Unsigned((raw[1] & 0x0F) as u64),
+                        &self,
+                    )
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 2, ObjectIdVisitor)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::seq_num::SeqNum;
+
+#[derive(Debug)]
+pub(crate) struct OutputBestEffortStream<'storage> {
+    buffer: &'storage mut [u8],
+    offset: usize,
+    writer: usize,
+    /// Position of the writer before the last reservation and its padding.
+    unpadded: usize,
+    last_send: SeqNum,
+}
+
+impl<'storage> OutputBestEffortStream<'storage> {
+    pub(crate) fn new(buffer: &'storage mut [u8], header_offset: usize) -> Self {
+        OutputBestEffortStream {
+            buffer,
+            offset: header_offset,
+            writer: header_offset,
+            unpadded: header_offset,
+            last_send: SeqNum::MAX,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.writer = self.offset;
+        self.unpadded = self.offset;
+        self.last_send = SeqNum::MAX;
+    }
+
+    /// Reserves `len` bytes aligned to 4 bytes after the last written submessage.
+    pub(crate) fn prepare_buffer(&mut self, len: usize) -> Option<&mut [u8]> {
+        let begin = (self.writer + 3) & !3;
+        let end = begin + len;
+        if end > self.buffer.len() {
+            return None;
+        }
+
+        self.buffer[self.writer..begin].fill(0);
+        self.unpadded = self.writer;
+        self.writer = end;
+        Some(&mut self.buffer[begin..end])
+    }
+
+    /// Largest length `prepare_buffer` can reserve at once.
+    pub(crate) fn max_prepare_len(&self) -> usize {
+        self.buffer.len().saturating_sub((self.writer + 3) & !3)
+    }
+
+    /// Largest length the message can hold after its header.
+    pub(crate) fn max_message_len(&self) -> usize {
+        self.buffer.len().saturating_sub(self.offset)
+    }
+
+    /// Gives back the last `len` bytes reserved by `prepare_buffer`, along with the
+    /// padding before them when the whole reservation is given back.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.writer = self.writer.saturating_sub(len).max(self.offset);
+        if (self.unpadded..=(self.unpadded + 3) & !3).contains(&self.writer) {
+            self.writer = self.unpadded;
+        }
+    }
+
+    /// Returns the pending message, with room for the message header at the beginning,
+    /// and the sequence number it must be sent with.
+    pub(crate) fn prepare_stream_to_send(&mut self) -> Option<(&mut [u8], SeqNum)> {
+        if self.offset < self.writer {
+            let len = self.writer;
+            self.last_send = self.last_send.add(1);
+            self.writer = self.offset;
+            Some((&mut self.buffer[..len], self.last_send))
+        } else {
+            None
+        }
+    }
+}
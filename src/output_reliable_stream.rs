@@ -0,0 +1,249 @@
+use crate::seq_num::SeqNum;
+use crate::MIN_HEARTBEAT_TIME_INTERVAL;
+
+/// Each history slot begins with the length of the message it stores.
+const INTERNAL_RELIABLE_BUFFER_OFFSET: usize = core::mem::size_of::<usize>();
+
+///
+/// The buffer is split into `history` slots, each one holding a single message:
+/// (last_acknown, last_sent] are sent and waiting for an ACKNACK,
+/// (last_sent, last_written] are pending to be sent.
+///
+#[derive(Debug)]
+pub(crate) struct OutputReliableStream<'storage> {
+    buffer: &'storage mut [u8],
+    history: u16,
+    offset: usize,
+    last_written: SeqNum,
+    last_sent: SeqNum,
+    last_acknown: SeqNum,
+    /// Length of the pending message before the last reservation and its padding.
+    unpadded_len: usize,
+    next_heartbeat_timestamp: i64,
+    next_heartbeat_tries: u8,
+}
+
+impl<'storage> OutputReliableStream<'storage> {
+    pub(crate) fn new(buffer: &'storage mut [u8], history: u16, header_offset: usize) -> Self {
+        let mut stream = OutputReliableStream {
+            buffer,
+            history,
+            offset: header_offset,
+            last_written: SeqNum::MAX,
+            last_sent: SeqNum::MAX,
+            last_acknown: SeqNum::MAX,
+            unpadded_len: header_offset,
+            next_heartbeat_timestamp: i64::MAX,
+            next_heartbeat_tries: 0,
+        };
+        stream.reset();
+        stream
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for index in 0..self.history {
+            self.set_slot_length(SeqNum::new(index), self.offset);
+        }
+
+        self.last_written = SeqNum::MAX;
+        self.last_sent = SeqNum::MAX;
+        self.last_acknown = SeqNum::MAX;
+        self.unpadded_len = self.offset;
+        self.next_heartbeat_timestamp = i64::MAX;
+        self.next_heartbeat_tries = 0;
+    }
+
+    fn slot_size(&self) -> usize {
+        self.buffer.len() / self.history as usize
+    }
+
+    fn slot_capacity(&self) -> usize {
+        self.slot_size() - INTERNAL_RELIABLE_BUFFER_OFFSET
+    }
+
+    fn slot_begin(&self, seq_num: SeqNum) -> usize {
+        (seq_num.get() % self.history) as usize * self.slot_size()
+    }
+
+    fn slot_length(&self, seq_num: SeqNum) -> usize {
+        let begin = self.slot_begin(seq_num);
+        let mut raw = [0u8; INTERNAL_RELIABLE_BUFFER_OFFSET];
+        raw.copy_from_slice(&self.buffer[begin..begin + INTERNAL_RELIABLE_BUFFER_OFFSET]);
+        usize::from_ne_bytes(raw)
+    }
+
+    fn set_slot_length(&mut self, seq_num: SeqNum, length: usize) {
+        let begin = self.slot_begin(seq_num);
+        self.buffer[begin..begin + INTERNAL_RELIABLE_BUFFER_OFFSET]
+            .copy_from_slice(&length.to_ne_bytes());
+    }
+
+    /// Returns the stored message, with room for the message header at the beginning.
+    pub(crate) fn get_message(&mut self, seq_num: SeqNum) -> &mut [u8] {
+        let begin = self.slot_begin(seq_num) + INTERNAL_RELIABLE_BUFFER_OFFSET;
+        let len = self.slot_length(seq_num);
+        &mut self.buffer[begin..begin + len]
+    }
+
+    /// Reserves `len` bytes aligned to 4 bytes, appending them to the pending message
+    /// when they fit or opening a new history slot otherwise.
+    pub(crate) fn prepare_buffer(&mut self, len: usize) -> Option<&mut [u8]> {
+        self.prepare(len, false)
+    }
+
+    /// Reserves `len` bytes at the beginning of a new history slot, so they are sent
+    /// in their own message.
+    pub(crate) fn prepare_new_message(&mut self, len: usize) -> Option<&mut [u8]> {
+        self.prepare(len, true)
+    }
+
+    fn prepare(&mut self, len: usize, new_message: bool) -> Option<&mut [u8]> {
+        let capacity = self.slot_capacity();
+        let mut seq_num = self.last_written;
+        let mut begin = (self.slot_length(seq_num) + 3) & !3;
+
+        if new_message || seq_num == self.last_sent || begin + len > capacity {
+            if self.offset + len > capacity {
+                return None;
+            }
+
+            seq_num = self.last_written.add(1);
+            if self.last_acknown.distance(seq_num) > self.history {
+                return None;
+            }
+
+            self.last_written = seq_num;
+            self.set_slot_length(seq_num, self.offset);
+            begin = self.offset;
+        }
+
+        let current = self.slot_length(seq_num);
+        self.set_slot_length(seq_num, begin + len);
+        self.unpadded_len = current;
+
+        let slot = self.slot_begin(seq_num) + INTERNAL_RELIABLE_BUFFER_OFFSET;
+        self.buffer[slot + current..slot + begin].fill(0);
+        Some(&mut self.buffer[slot + begin..slot + begin + len])
+    }
+
+    /// Largest length `prepare_buffer` can reserve at once, in a new history slot
+    /// when there is one free or after the pending message otherwise.
+    pub(crate) fn max_prepare_len(&self) -> usize {
+        let capacity = self.slot_capacity();
+        if self.last_acknown.distance(self.last_written.add(1)) <= self.history {
+            capacity.saturating_sub(self.offset)
+        } else if self.last_written != self.last_sent {
+            capacity.saturating_sub((self.slot_length(self.last_written) + 3) & !3)
+        } else {
+            0
+        }
+    }
+
+    /// Largest length a message can hold after its header, whatever the state of the
+    /// history.
+    pub(crate) fn max_message_len(&self) -> usize {
+        self.slot_capacity().saturating_sub(self.offset)
+    }
+
+    /// Returns true when `count` new messages can be prepared before the history is full.
+    pub(crate) fn has_free_slots(&self, count: u16) -> bool {
+        count <= self.history
+            && self.last_acknown.distance(self.last_written.add(count)) <= self.history
+    }
+
+    /// Gives back the last `len` bytes reserved by `prepare_buffer`, along with the
+    /// padding before them when the whole reservation is given back. The history slot
+    /// is released when nothing remains in it.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if self.last_written == self.last_sent {
+            return;
+        }
+
+        let mut length = self.slot_length(self.last_written).saturating_sub(len);
+        if (self.unpadded_len..=(self.unpadded_len + 3) & !3).contains(&length) {
+            length = self.unpadded_len;
+        }
+
+        if length <= self.offset {
+            self.set_slot_length(self.last_written, self.offset);
+            self.last_written = self.last_written.sub(1);
+        } else {
+            self.set_slot_length(self.last_written, length);
+        }
+    }
+
+    /// Returns the next pending message and its sequence number, which is kept
+    /// in the history until it is acknowledged.
+    pub(crate) fn prepare_next_to_send(&mut self) -> Option<(&mut [u8], SeqNum)> {
+        if self.last_sent < self.last_written {
+            self.last_sent = self.last_sent.add(1);
+            let seq_num = self.last_sent;
+            Some((self.get_message(seq_num), seq_num))
+        } else {
+            None
+        }
+    }
+
+    /// Updates the acknowledged messages. Returns the sequence numbers that the agent
+    /// reported as lost through the `nack_bitmap`.
+    pub(crate) fn process_acknack(
+        &mut self,
+        nack_bitmap: u16,
+        first_unacked_seq_num: SeqNum,
+    ) -> u16 {
+        let last_acked_seq_num = first_unacked_seq_num.sub(1);
+
+        if !(self.last_acknown <= last_acked_seq_num && last_acked_seq_num <= self.last_sent) {
+            return 0;
+        }
+
+        self.last_acknown = last_acked_seq_num;
+        self.next_heartbeat_tries = 0;
+        self.next_heartbeat_timestamp = i64::MAX;
+
+        let mut lost = nack_bitmap;
+        for i in 0..16 {
+            if first_unacked_seq_num.add(i) > self.last_sent {
+                lost &= !(1 << i);
+            }
+        }
+        lost
+    }
+
+    /// Returns true when a HEARTBEAT has to be sent at `current_timestamp`.
+    pub(crate) fn update_heartbeat_timestamp(&mut self, current_timestamp: i64) -> bool {
+        if self.last_acknown < self.last_sent {
+            if self.next_heartbeat_tries == 0 {
+                self.next_heartbeat_timestamp = current_timestamp + MIN_HEARTBEAT_TIME_INTERVAL;
+                self.next_heartbeat_tries = 1;
+            } else if current_timestamp >= self.next_heartbeat_timestamp {
+                let increment = MIN_HEARTBEAT_TIME_INTERVAL << (self.next_heartbeat_tries % 16);
+                let difference = current_timestamp - self.next_heartbeat_timestamp;
+                self.next_heartbeat_timestamp += increment.max(difference);
+                self.next_heartbeat_tries = self.next_heartbeat_tries.saturating_add(1);
+                return true;
+            }
+        } else {
+            self.next_heartbeat_timestamp = i64::MAX;
+        }
+        false
+    }
+
+    /// Time of the next HEARTBEAT, `i64::MAX` when no message is waiting for an ACKNACK.
+    pub(crate) fn next_heartbeat_timestamp(&self) -> i64 {
+        self.next_heartbeat_timestamp
+    }
+
+    /// Returns true when every message sent has been acknowledged.
+    pub(crate) fn is_up_to_date(&self) -> bool {
+        self.last_acknown == self.last_sent
+    }
+
+    pub(crate) fn first_unacked_seq_num(&self) -> SeqNum {
+        self.last_acknown.add(1)
+    }
+
+    pub(crate) fn last_sent(&self) -> SeqNum {
+        self.last_sent
+    }
+}
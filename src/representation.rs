@@ -0,0 +1,62 @@
+use serde::ser::{Serialize, SerializeTuple};
+
+const REPRESENTATION_BY_REFERENCE: u8 = 0x01;
+const REPRESENTATION_AS_XML_STRING: u8 = 0x02;
+const REPRESENTATION_IN_BINARY: u8 = 0x03;
+
+///
+/// How the agent has to build an entity: from a profile it already knows, from an
+/// XML description or from its binary representation.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectRepresentation<'a> {
+    Ref(&'a str),
+    Xml(&'a str),
+    Binary(&'a [u8]),
+}
+
+impl ObjectRepresentation<'_> {
+    /// Serialized size when written at `offset`, including its alignment.
+    pub(crate) fn size(&self, offset: usize) -> usize {
+        // format, then the length of the string or sequence aligned to 4 bytes
+        let offset = ((offset + 1 + 3) & !3) + 4;
+        match *self {
+            ObjectRepresentation::Ref(s) | ObjectRepresentation::Xml(s) => offset + s.len() + 1,
+            ObjectRepresentation::Binary(b) => offset + b.len(),
+        }
+    }
+}
+
+impl Serialize for ObjectRepresentation<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        match *self {
+            ObjectRepresentation::Ref(reference) => {
+                s.serialize_element(&REPRESENTATION_BY_REFERENCE)?;
+                s.serialize_element(reference)?;
+            }
+            ObjectRepresentation::Xml(xml) => {
+                s.serialize_element(&REPRESENTATION_AS_XML_STRING)?;
+                s.serialize_element(xml)?;
+            }
+            ObjectRepresentation::Binary(binary) => {
+                s.serialize_element(&REPRESENTATION_IN_BINARY)?;
+                s.serialize_element(binary)?;
+            }
+        }
+        s.end()
+    }
+}
+
+///
+/// What the agent does when the entity already exists: with `reuse` it keeps the
+/// existing one if it matches, with `replace` it deletes and creates it again.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CreationMode {
+    pub reuse: bool,
+    pub replace: bool,
+}
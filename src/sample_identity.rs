@@ -0,0 +1,113 @@
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Serialize,
+};
+
+use crate::{error, micro_cdr};
+
+pub const SAMPLE_IDENTITY_SIZE: usize = 24;
+
+///
+/// Identifies the request a reply answers. The agent assigns it to every request
+/// written through a requester, using the request id of the WRITE_DATA as the low
+/// part of the sequence number.
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |                    guidPrefix (12 bytes) ...                      |
+/// +----------------+----------------+----------------+----------------+
+/// |                   entityKey                      |   entityKind   |
+/// +----------------+----------------+----------------+----------------+
+/// |                       sequenceNumber.high                         |
+/// +----------------+----------------+----------------+----------------+
+/// |                       sequenceNumber.low                          |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SampleIdentity {
+    pub guid_prefix: [u8; 12],
+    pub entity_key: [u8; 3],
+    pub entity_kind: u8,
+    pub sequence_number_high: i32,
+    pub sequence_number_low: u32,
+}
+
+impl SampleIdentity {
+    /// Request id returned when the request this identity belongs to was written.
+    ///
+    /// It is only the low 16 bits of the sequence number, and the request ids of a
+    /// session wrap around: two requests far enough apart share it, the whole
+    /// identity tells them apart.
+    pub fn request_id(&self) -> u16 {
+        self.sequence_number_low as u16
+    }
+
+    /// Reads the identity at the beginning of a request or reply sample and
+    /// returns it along with the data following it.
+    pub fn from_slice(buf: &[u8]) -> error::Result<(SampleIdentity, &[u8])> {
+        let mut ucdr = micro_cdr::Decoder::new(buf);
+        let sample_identity = SampleIdentity::deserialize(&mut ucdr)?;
+        let data = buf
+            .get(SAMPLE_IDENTITY_SIZE..)
+            .ok_or(error::Error::BufferNotEnough)?;
+        Ok((sample_identity, data))
+    }
+}
+
+impl Serialize for SampleIdentity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.guid_prefix)?;
+        s.serialize_element(&self.entity_key)?;
+        s.serialize_element(&self.entity_kind)?;
+        s.serialize_element(&self.sequence_number_high)?;
+        s.serialize_element(&self.sequence_number_low)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SampleIdentity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct SampleIdentityVisitor;
+
+        impl<'de> Visitor<'de> for SampleIdentityVisitor {
+            type Value = SampleIdentity;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct SampleIdentity")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(SampleIdentity {
+                    guid_prefix: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    entity_key: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    entity_kind: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                    sequence_number_high: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(3, &self))?,
+                    sequence_number_low: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(4, &self))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 5, SampleIdentityVisitor)
+    }
+}
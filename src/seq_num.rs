@@ -0,0 +1,60 @@
+use core::cmp::Ordering;
+
+const SEQ_NUM_HALF: u16 = 1 << 15;
+
+///
+/// Sequence number of a message within a stream. It wraps around after 65535,
+/// so it is compared with the serial number arithmetic of RFC 1982: a number is
+/// greater than the 2^15 - 1 numbers that precede it, and the order between two
+/// numbers exactly 2^15 apart is undefined.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SeqNum(u16);
+
+impl SeqNum {
+    pub const MAX: SeqNum = SeqNum(u16::MAX);
+
+    pub const fn new(raw: u16) -> Self {
+        SeqNum(raw)
+    }
+
+    pub const fn get(self) -> u16 {
+        self.0
+    }
+
+    pub const fn add(self, increment: u16) -> Self {
+        SeqNum(self.0.wrapping_add(increment))
+    }
+
+    pub const fn sub(self, decrement: u16) -> Self {
+        SeqNum(self.0.wrapping_sub(decrement))
+    }
+
+    /// Number of increments needed to go from `self` to `other`.
+    pub const fn distance(self, other: SeqNum) -> u16 {
+        other.0.wrapping_sub(self.0)
+    }
+}
+
+impl PartialOrd for SeqNum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.distance(*other) {
+            0 => Some(Ordering::Equal),
+            SEQ_NUM_HALF => None,
+            d if d < SEQ_NUM_HALF => Some(Ordering::Less),
+            _ => Some(Ordering::Greater),
+        }
+    }
+}
+
+impl From<u16> for SeqNum {
+    fn from(raw: u16) -> Self {
+        SeqNum(raw)
+    }
+}
+
+impl From<SeqNum> for u16 {
+    fn from(seq_num: SeqNum) -> Self {
+        seq_num.0
+    }
+}
@@ -324,8 +324,8 @@ impl<Comm: SerialPlatformOps> Transmitter for SerialTransport<Comm> {
     }
 }
 
-impl<'storage, Comm: SerialPlatformOps> Receiver<'storage> for SerialTransport<Comm> {
-    fn receive_msg(&'storage mut self, timeout: i32) -> Result<&'storage [u8]> {
+impl<Comm: SerialPlatformOps> Receiver for SerialTransport<Comm> {
+    fn receive_msg(&mut self, timeout: i32) -> Result<&[u8]> {
         let mut timeout = timeout;
 
         loop {
@@ -1,19 +1,40 @@
 use super::Error;
 use super::Result;
 use crate::communication::{Receiver, Transmitter};
+use crate::data::{DataHandler, DataRepresentation, DeliveryControl};
+use crate::data_handler::DataHandlers;
+use crate::error;
 use crate::header::{MessageHeader, CLIENT_KEY_SIZE, SESSION_ID_WITHOUT_CLIENT_KEY};
-use crate::micro_cdr::Decoder;
+use crate::info::{self, InfoHandler, InfoHandlerSlot};
+use crate::micro_cdr;
+use crate::object_id::ObjectId;
+use crate::representation::{CreationMode, ObjectRepresentation};
+use crate::sample_identity::{SampleIdentity, SAMPLE_IDENTITY_SIZE};
+use crate::seq_num::SeqNum;
+use crate::status::{status_to_result, RequestStatus, Status, StatusTable};
 use crate::stream_id::StreamDirection;
 use crate::stream_id::StreamId;
 use crate::stream_id::StreamType;
-use crate::submessage;
-use crate::submessage::{SubMessageHeader, SUBHEADER_SIZE};
+use crate::stream_storage::StreamStorage;
+use crate::submessage::{DataFormat, SubMessageHeader, SubMessages, SUBHEADER_SIZE};
+use crate::supervision::{AgentState, Creation, Supervisor};
 use crate::time::Clock;
-use crate::types::{CLIENT_Representation, CREATE_CLIENT_Payload};
-use crate::{error, micro_cdr, stream_id};
-use crate::{MAX_SESSION_CONNECTION_ATTEMPTS, MIN_SESSION_CONNECTION_INTERVAL};
-use core::convert::Infallible;
-use core::marker::PhantomData;
+use crate::types::{
+    ACKNACK_Payload, BaseObjectRequest, CLIENT_Representation, CREATE_CLIENT_Payload,
+    CREATE_Payload, DELETE_Payload, GET_INFO_Payload, HEARTBEAT_Payload, ObjectVariant,
+    READ_DATA_Payload, STATUS_AGENT_Payload, STATUS_Payload, TIMESTAMP_Payload,
+    TIMESTAMP_REPLY_Payload, Time, WRITE_DATA_Payload, ACKNACK_PAYLOAD_SIZE,
+    BASE_OBJECT_REQUEST_SIZE, GET_INFO_PAYLOAD_SIZE, HEARTBEAT_PAYLOAD_SIZE, INFO_ACTIVITY,
+    INFO_CONFIGURATION, OBJECTID_AGENT, OBJECTID_CLIENT, REQUEST_GET_INFO, REQUEST_LOGOUT,
+    STATUS_ERR_INCOMPATIBLE, TIMESTAMP_PAYLOAD_SIZE, XRCE_COOKIE, XRCE_VENDOR_ID,
+    XRCE_VERSION_MAJOR, XRCE_VERSION_MINOR,
+};
+use crate::Endianness;
+use crate::{
+    MAX_SESSION_CONNECTION_ATTEMPTS, MAX_SUPERVISED_ENTITIES, MAX_TRANSPORT_MTU,
+    MIN_SESSION_CONNECTION_INTERVAL,
+};
+use serde::{Deserialize, Serialize};
 
 #[cfg(any(feature = "hard-liveliness-check", feature = "profile-shared-memory"))]
 use crate::types::Property;
@@ -40,6 +61,16 @@ const CREATE_SESSION_MAX_MSG_SIZE: usize = MAX_HEADER_SIZE
     + SUBHEADER_SIZE
     + CREATE_CLIENT_PAYLOAD_SIZE
     + CREATE_SESSION_PROPERTIES_MAX_SIZE;
+const DELETE_CLIENT_PAYLOAD_SIZE: usize = 4;
+const DELETE_SESSION_MAX_MSG_SIZE: usize =
+    MAX_HEADER_SIZE + SUBHEADER_SIZE + DELETE_CLIENT_PAYLOAD_SIZE;
+const HEARTBEAT_MAX_MSG_SIZE: usize = MAX_HEADER_SIZE + SUBHEADER_SIZE + HEARTBEAT_PAYLOAD_SIZE;
+const TIMESTAMP_MAX_MSG_SIZE: usize = MAX_HEADER_SIZE + SUBHEADER_SIZE + TIMESTAMP_PAYLOAD_SIZE;
+const GET_INFO_MAX_MSG_SIZE: usize = MAX_HEADER_SIZE + SUBHEADER_SIZE + GET_INFO_PAYLOAD_SIZE;
+const ACKNACK_MAX_MSG_SIZE: usize = MAX_HEADER_SIZE + SUBHEADER_SIZE + ACKNACK_PAYLOAD_SIZE;
+/// First reliable input stream. The READ_DATA sent by `cancel_data` must name one, but
+/// it asks for no sample so nothing is delivered on it.
+const CANCEL_DATA_PREFERRED_STREAM_ID: u8 = 0x80;
 
 type ClientKey = [u8; 4];
 ///
@@ -48,25 +79,87 @@ type ClientKey = [u8; 4];
 struct SessionInfo {
     id: u8,
     key: ClientKey,
+    last_requested_status: Option<u8>,
+    last_request_id: u16,
+    agent_vendor_id: Option<[u8; 2]>,
+}
+
+impl SessionInfo {
+    /// Request ids up to LOGOUT are reserved, the generated ones wrap around after them.
+    fn generate_request_id(&mut self) -> u16 {
+        let request_id = if self.last_request_id <= u16::from_be_bytes(REQUEST_LOGOUT) {
+            u16::from_be_bytes(REQUEST_LOGOUT) + 1
+        } else {
+            self.last_request_id
+        };
+        self.last_request_id = request_id.wrapping_add(1);
+        request_id
+    }
+
+    fn header_offset(&self) -> usize {
+        if SESSION_ID_WITHOUT_CLIENT_KEY > self.id {
+            MAX_HEADER_SIZE
+        } else {
+            MIN_HEADER_SIZE
+        }
+    }
+
+    fn stamp_session_header(&self, stream_id_raw: u8, seq_num: SeqNum, buf: &mut [u8]) -> usize {
+        let key = if SESSION_ID_WITHOUT_CLIENT_KEY > self.id {
+            Some(self.key)
+        } else {
+            None
+        };
+
+        MessageHeader::new(self.id, stream_id_raw, seq_num.get(), key)
+            .to_slice(buf)
+            .unwrap()
+    }
 }
 
 #[derive(Debug)]
-pub struct Session<'storage, 'a: 'storage, Transport: Transmitter + Receiver<'a>> {
+pub struct Session<'storage, 'a, Transport: Transmitter + Receiver> {
     transport: &'a mut Transport,
     info: SessionInfo,
     mtu: u16,
-    _p: PhantomData<&'storage [u8]>,
+    streams: StreamStorage<'storage>,
+    data_handlers: DataHandlers<'storage>,
+    status_table: StatusTable,
+    info_handler: InfoHandlerSlot<'storage>,
+    supervisor: Supervisor<'storage>,
+    agent_availability: Option<i16>,
+    synchronized: bool,
+    /// Transmit timestamp of the last TIMESTAMP sent, the reply must carry it back.
+    sent_timestamp: Option<Time>,
+    /// Local time minus agent time, in nanoseconds.
+    time_offset: i64,
+    round_trip: i64,
 }
 
 type SessionResult<T> = core::result::Result<T, Error>;
 
-impl<'storage, 'a: 'storage, T: Transmitter + Receiver<'a> + Clock> Session<'storage, 'a, T> {
+impl<'storage, 'a, T: Transmitter + Receiver + Clock> Session<'storage, 'a, T> {
     pub fn new(key: ClientKey, transport: &'a mut T) -> Self {
         Session {
             transport,
-            info: SessionInfo { id: 0x81, key },
+            info: SessionInfo {
+                id: 0x81,
+                key,
+                last_requested_status: None,
+                last_request_id: u16::from_be_bytes(REQUEST_LOGOUT),
+                agent_vendor_id: None,
+            },
             mtu: 256,
-            _p: PhantomData,
+            streams: StreamStorage::default(),
+            data_handlers: DataHandlers::default(),
+            status_table: StatusTable::default(),
+            info_handler: InfoHandlerSlot::default(),
+            supervisor: Supervisor::default(),
+            agent_availability: None,
+            synchronized: false,
+            sent_timestamp: None,
+            time_offset: 0,
+            round_trip: 0,
         }
     }
 
@@ -87,16 +180,1135 @@ impl<'storage, 'a: 'storage, T: Transmitter + Receiver<'a> + Clock> Session<'sto
 
         let len = len1 + len2;
         self.wait_session_status(
-            &mut create_session_buffer[..len],
+            &create_session_buffer[..len],
+            MAX_SESSION_CONNECTION_ATTEMPTS,
+        )?;
+
+        self.streams.reset();
+        Ok(())
+    }
+
+    /// Deletes the session on the agent and releases the borrowed transport.
+    pub fn delete(mut self) -> SessionResult<()> {
+        let mut delete_session_buffer = [0u8; DELETE_SESSION_MAX_MSG_SIZE];
+
+        let len1 = self
+            .info
+            .stamp_session_header(0, SeqNum::new(0), &mut delete_session_buffer);
+
+        let len2 = DELETE_Payload(BaseObjectRequest {
+            request_id: REQUEST_LOGOUT,
+            object_id: OBJECTID_CLIENT,
+        })
+        .to_slice(&mut delete_session_buffer[len1..])
+        .unwrap();
+
+        let len = len1 + len2;
+        self.wait_session_status(
+            &delete_session_buffer[..len],
             MAX_SESSION_CONNECTION_ATTEMPTS,
         )
     }
 
+    /// Creates a best-effort output stream over `buffer`, which bounds the size of a message
+    /// along with the MTU of the session. The part of `buffer` beyond the MTU is not used.
+    pub fn create_output_best_effort_stream(
+        &mut self,
+        buffer: &'storage mut [u8],
+    ) -> SessionResult<StreamId> {
+        if buffer.len() <= self.info.header_offset() {
+            return Err(Error::BufferNotEnough);
+        }
+
+        let len = buffer.len().min(self.mtu as usize);
+        let (buffer, _) = buffer.split_at_mut(len);
+        self.streams
+            .add_output_best_effort_buffer(buffer, self.info.header_offset())
+            .ok_or(Error::OutOfResources)
+    }
+
+    /// Creates a reliable output stream over `buffer`, split into `history` slots of one
+    /// message each. `history` must be a power of two.
+    pub fn create_output_reliable_stream(
+        &mut self,
+        buffer: &'storage mut [u8],
+        history: u16,
+    ) -> SessionResult<StreamId> {
+        if !history.is_power_of_two() {
+            return Err(Error::InvalidData);
+        }
+
+        if buffer.len() / history as usize
+            <= core::mem::size_of::<usize>() + self.info.header_offset()
+        {
+            return Err(Error::BufferNotEnough);
+        }
+
+        self.streams
+            .add_output_reliable_buffer(buffer, history, self.info.header_offset())
+            .ok_or(Error::OutOfResources)
+    }
+
+    /// Creates a best-effort input stream, the messages received on it are read in
+    /// place so it does not need any buffer.
+    pub fn create_input_best_effort_stream(&mut self) -> SessionResult<StreamId> {
+        self.streams
+            .add_input_best_effort()
+            .ok_or(Error::OutOfResources)
+    }
+
+    /// Creates a reliable input stream over `buffer`, split into `history` slots where
+    /// the messages received out of order are kept. `history` must be a power of two.
+    pub fn create_input_reliable_stream(
+        &mut self,
+        buffer: &'storage mut [u8],
+        history: u16,
+    ) -> SessionResult<StreamId> {
+        if !history.is_power_of_two() {
+            return Err(Error::InvalidData);
+        }
+
+        if buffer.len() / history as usize <= core::mem::size_of::<usize>() {
+            return Err(Error::BufferNotEnough);
+        }
+
+        self.streams
+            .add_input_reliable_buffer(buffer, history)
+            .ok_or(Error::OutOfResources)
+    }
+
+    /// Gives the reliable input stream `stream_id` a buffer where the FRAGMENT
+    /// submessages it receives are joined. It bounds the size of the longest
+    /// submessage that can be received in fragments, the longer ones are dropped.
+    pub fn set_reassembly_buffer(
+        &mut self,
+        stream_id: StreamId,
+        buffer: &'storage mut [u8],
+    ) -> SessionResult<()> {
+        if stream_id.direction != StreamDirection::InputStream
+            || stream_id.type_u != StreamType::ReliableStream
+        {
+            return Err(Error::InvalidStream);
+        }
+
+        self.streams
+            .get_input_reliable(stream_id.index)
+            .ok_or(Error::InvalidStream)?
+            .set_reassembly_buffer(buffer);
+        Ok(())
+    }
+
+    /// Reserves room for a submessage in an output stream, writes its header and
+    /// returns the slice where the payload must be written.
+    pub fn prepare_output_stream(
+        &mut self,
+        stream_id: StreamId,
+        submessage_header: SubMessageHeader,
+    ) -> SessionResult<&mut [u8]> {
+        let len = SUBHEADER_SIZE + submessage_header.payload_len() as usize;
+        let buf = self.reserve_output_stream(stream_id, len)?;
+
+        submessage_header
+            .to_slice(&mut buf[..SUBHEADER_SIZE])
+            .map_err(|_| Error::BufferNotEnough)?;
+        Ok(&mut buf[SUBHEADER_SIZE..])
+    }
+
+    fn reserve_output_stream(
+        &mut self,
+        stream_id: StreamId,
+        len: usize,
+    ) -> SessionResult<&mut [u8]> {
+        if stream_id.direction != StreamDirection::OutputStream {
+            return Err(Error::InvalidStream);
+        }
+
+        match stream_id.type_u {
+            StreamType::BestEffortStream => self
+                .streams
+                .get_output_best_effort(stream_id.index)
+                .ok_or(Error::InvalidStream)?
+                .prepare_buffer(len)
+                .ok_or(Error::BufferNotEnough),
+            StreamType::ReliableStream => self
+                .streams
+                .get_output_reliable(stream_id.index)
+                .ok_or(Error::InvalidStream)?
+                .prepare_buffer(len)
+                .ok_or(Error::BufferNotEnough),
+            _ => Err(Error::InvalidStream),
+        }
+    }
+
+    fn output_stream_max_len(&mut self, stream_id: StreamId) -> SessionResult<usize> {
+        if stream_id.direction != StreamDirection::OutputStream {
+            return Err(Error::InvalidStream);
+        }
+
+        match stream_id.type_u {
+            StreamType::BestEffortStream => self
+                .streams
+                .get_output_best_effort(stream_id.index)
+                .map(|stream| stream.max_prepare_len())
+                .ok_or(Error::InvalidStream),
+            StreamType::ReliableStream => self
+                .streams
+                .get_output_reliable(stream_id.index)
+                .map(|stream| stream.max_prepare_len())
+                .ok_or(Error::InvalidStream),
+            _ => Err(Error::InvalidStream),
+        }
+    }
+
+    /// Longest submessage a single message of the output stream can hold, within the
+    /// MTU of the session.
+    fn output_stream_message_len(&mut self, stream_id: StreamId) -> SessionResult<usize> {
+        if stream_id.direction != StreamDirection::OutputStream {
+            return Err(Error::InvalidStream);
+        }
+
+        let mtu_len = (self.mtu as usize).saturating_sub(self.info.header_offset());
+        match stream_id.type_u {
+            StreamType::BestEffortStream => self
+                .streams
+                .get_output_best_effort(stream_id.index)
+                .map(|stream| stream.max_message_len().min(mtu_len))
+                .ok_or(Error::InvalidStream),
+            StreamType::ReliableStream => self
+                .streams
+                .get_output_reliable(stream_id.index)
+                .map(|stream| stream.max_message_len().min(mtu_len))
+                .ok_or(Error::InvalidStream),
+            _ => Err(Error::InvalidStream),
+        }
+    }
+
+    fn truncate_output_stream(&mut self, stream_id: StreamId, len: usize) {
+        match stream_id.type_u {
+            StreamType::BestEffortStream => {
+                if let Some(stream) = self.streams.get_output_best_effort(stream_id.index) {
+                    stream.truncate(len);
+                }
+            }
+            StreamType::ReliableStream => {
+                if let Some(stream) = self.streams.get_output_reliable(stream_id.index) {
+                    stream.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes a CREATE for a participant in `domain_id` into `stream_id`, returns the
+    /// request id the agent will answer with a STATUS.
+    pub fn create_participant(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        domain_id: i16,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Participant {
+                representation,
+                domain_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a topic of the participant `participant_id` into `stream_id`,
+    /// returns the request id the agent will answer with a STATUS.
+    pub fn create_topic(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        participant_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Topic {
+                representation,
+                participant_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a publisher of the participant `participant_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    /// A publisher can't be created by reference, [`ObjectRepresentation::Ref`] is
+    /// rejected with [`Error::InvalidUsage`].
+    pub fn create_publisher(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        participant_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Publisher {
+                representation,
+                participant_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a datawriter of the publisher `publisher_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    pub fn create_datawriter(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        publisher_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::DataWriter {
+                representation,
+                publisher_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a subscriber of the participant `participant_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    /// A subscriber can't be created by reference, [`ObjectRepresentation::Ref`] is
+    /// rejected with [`Error::InvalidUsage`].
+    pub fn create_subscriber(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        participant_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Subscriber {
+                representation,
+                participant_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a datareader of the subscriber `subscriber_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    pub fn create_datareader(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        subscriber_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::DataReader {
+                representation,
+                subscriber_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a requester of the participant `participant_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    pub fn create_requester(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        participant_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Requester {
+                representation,
+                participant_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes a CREATE for a replier of the participant `participant_id` into
+    /// `stream_id`, returns the request id the agent will answer with a STATUS.
+    pub fn create_replier(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        participant_id: ObjectId,
+        representation: ObjectRepresentation,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        self.write_create(
+            stream_id,
+            object_id,
+            ObjectVariant::Replier {
+                representation,
+                participant_id,
+            },
+            mode,
+        )
+    }
+
+    /// Writes `data` to the datawriter `datawriter_id`, in the format of its representation.
+    /// On a reliable stream, [`DataRepresentation::Data`] too long for a single message
+    /// is sent in fragments.
+    pub fn write_data(
+        &mut self,
+        stream_id: StreamId,
+        datawriter_id: ObjectId,
+        data: DataRepresentation,
+    ) -> SessionResult<u16> {
+        if let DataRepresentation::Data(data) = data {
+            return self.write_data_payload(stream_id, datawriter_id, None, data);
+        }
+
+        let request_id = self.info.generate_request_id();
+        let payload = WRITE_DATA_Payload {
+            base: BaseObjectRequest {
+                request_id: request_id.to_be_bytes(),
+                object_id: datawriter_id,
+            },
+            data,
+        };
+
+        let len = u16::try_from(payload.size()).map_err(|_| Error::BufferNotEnough)?;
+        let buf =
+            self.prepare_output_stream(stream_id, SubMessageHeader::WriteData(len, data.format()))?;
+        payload.to_slice(buf).map_err(|_| Error::BufferNotEnough)?;
+        Ok(request_id)
+    }
+
+    /// Serializes `data` as a sample of the datawriter `datawriter_id` straight into
+    /// the output stream. The whole free space of the stream, up to the MTU of the
+    /// session, is reserved and the part left unused is given back once the size of
+    /// the sample is known.
+    ///
+    /// The sample is never fragmented: when even an empty message can't hold it,
+    /// [`Error::SampleTooLong`] is returned. Such a sample has to be serialized into
+    /// a buffer of its own and written as [`DataRepresentation::Data`] with
+    /// [`Session::write_data`], which fragments it on a reliable stream.
+    pub fn write<D: Serialize + ?Sized>(
+        &mut self,
+        stream_id: StreamId,
+        datawriter_id: ObjectId,
+        data: &D,
+    ) -> SessionResult<u16> {
+        let max_len = self
+            .output_stream_max_len(stream_id)?
+            .min(self.output_stream_message_len(stream_id)?)
+            .min(SUBHEADER_SIZE + u16::MAX as usize);
+        if max_len <= SUBHEADER_SIZE {
+            return Err(Error::BufferNotEnough);
+        }
+
+        let request_id = self.info.generate_request_id();
+        let base = BaseObjectRequest {
+            request_id: request_id.to_be_bytes(),
+            object_id: datawriter_id,
+        };
+
+        let buf = self.reserve_output_stream(stream_id, max_len)?;
+        let (header, payload) = buf.split_at_mut(SUBHEADER_SIZE);
+        let len = match serialize_sample(payload, &base, data) {
+            Ok(len) => len,
+            Err(_) => {
+                self.truncate_output_stream(stream_id, max_len);
+                return if max_len >= self.output_stream_message_len(stream_id)? {
+                    Err(Error::SampleTooLong)
+                } else {
+                    Err(Error::BufferNotEnough)
+                };
+            }
+        };
+
+        SubMessageHeader::WriteData(len as u16, DataFormat::FormatData)
+            .to_slice(header)
+            .map_err(|_| Error::BufferNotEnough)?;
+        self.truncate_output_stream(stream_id, max_len - SUBHEADER_SIZE - len);
+        Ok(request_id)
+    }
+
+    /// Asks the agent to start delivering the samples of the datareader `datareader_id`
+    /// into `input_stream`, at the pace set by `delivery_control`.
+    pub fn request_data(
+        &mut self,
+        stream_id: StreamId,
+        datareader_id: ObjectId,
+        input_stream: StreamId,
+        delivery_control: Option<DeliveryControl>,
+    ) -> SessionResult<u16> {
+        if input_stream.direction != StreamDirection::InputStream {
+            return Err(Error::InvalidStream);
+        }
+
+        self.write_read_data(stream_id, datareader_id, input_stream.raw, delivery_control)
+    }
+
+    /// Stops the delivery of the samples of the datareader `datareader_id`.
+    pub fn cancel_data(
+        &mut self,
+        stream_id: StreamId,
+        datareader_id: ObjectId,
+    ) -> SessionResult<u16> {
+        // a delivery control without samples to deliver cancels the previous requests
+        let delivery_control = DeliveryControl {
+            max_samples: 0,
+            max_elapsed_time: 0,
+            max_bytes_per_second: 0,
+            min_pace_period: 0,
+        };
+        self.write_read_data(
+            stream_id,
+            datareader_id,
+            CANCEL_DATA_PREFERRED_STREAM_ID,
+            Some(delivery_control),
+        )
+    }
+
+    fn write_read_data(
+        &mut self,
+        stream_id: StreamId,
+        datareader_id: ObjectId,
+        preferred_stream_id: u8,
+        delivery_control: Option<DeliveryControl>,
+    ) -> SessionResult<u16> {
+        let request_id = self.info.generate_request_id();
+        let payload = READ_DATA_Payload {
+            base: BaseObjectRequest {
+                request_id: request_id.to_be_bytes(),
+                object_id: datareader_id,
+            },
+            preferred_stream_id,
+            data_format: DataFormat::FormatData,
+            delivery_control,
+        };
+
+        let buf = self
+            .prepare_output_stream(stream_id, SubMessageHeader::ReadData(payload.size() as u16))?;
+        payload.to_slice(buf).map_err(|_| Error::BufferNotEnough)?;
+        Ok(request_id)
+    }
+
+    /// Registers `handler` for the samples delivered for the entity `object_id`. With a
+    /// `request_id` it only gets the ones delivered for that request, and is preferred
+    /// over the handler registered for every request of the entity.
+    pub fn set_data_handler(
+        &mut self,
+        object_id: ObjectId,
+        request_id: Option<u16>,
+        handler: DataHandler<'storage>,
+    ) -> SessionResult<()> {
+        if self.data_handlers.add(object_id, request_id, handler) {
+            Ok(())
+        } else {
+            Err(Error::OutOfResources)
+        }
+    }
+
+    /// Removes the handler registered with the same `object_id` and `request_id`,
+    /// returns false when there was none.
+    pub fn remove_data_handler(&mut self, object_id: ObjectId, request_id: Option<u16>) -> bool {
+        self.data_handlers.remove(object_id, request_id)
+    }
+
+    /// Writes a request through the requester `requester_id`. The reply carries a
+    /// [`SampleIdentity`] whose request id is the one returned here. As with the raw
+    /// data of [`Session::write_data`], a long request is sent in fragments.
+    ///
+    /// The request ids of the session wrap around, so a late reply may carry the id of
+    /// a newer request, see [`SampleIdentity::request_id`].
+    pub fn write_request(
+        &mut self,
+        stream_id: StreamId,
+        requester_id: ObjectId,
+        request: &[u8],
+    ) -> SessionResult<u16> {
+        self.write_data_payload(stream_id, requester_id, None, request)
+    }
+
+    /// Writes the reply to the request identified by `sample_identity` through the
+    /// replier `replier_id`.
+    pub fn write_reply(
+        &mut self,
+        stream_id: StreamId,
+        replier_id: ObjectId,
+        sample_identity: SampleIdentity,
+        reply: &[u8],
+    ) -> SessionResult<u16> {
+        self.write_data_payload(stream_id, replier_id, Some(sample_identity), reply)
+    }
+
+    /// Writes a WRITE_DATA in FORMAT_DATA whose sample is `data`, preceded by the
+    /// `sample_identity` of the request for a reply, in fragments when it is too long
+    /// for a single message of a reliable stream.
+    fn write_data_payload(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        sample_identity: Option<SampleIdentity>,
+        data: &[u8],
+    ) -> SessionResult<u16> {
+        let request_id = self.info.generate_request_id();
+        let base = BaseObjectRequest {
+            request_id: request_id.to_be_bytes(),
+            object_id,
+        };
+
+        let mut prefix = [0u8; BASE_OBJECT_REQUEST_SIZE + SAMPLE_IDENTITY_SIZE];
+        let mut ucdr = micro_cdr::Encoder::new(&mut prefix);
+        base.serialize(&mut ucdr)
+            .map_err(|_| Error::BufferNotEnough)?;
+        if let Some(sample_identity) = sample_identity {
+            sample_identity
+                .serialize(&mut ucdr)
+                .map_err(|_| Error::BufferNotEnough)?;
+        }
+        let prefix_len = ucdr.finalize();
+        let prefix = &prefix[..prefix_len];
+
+        let len = u16::try_from(prefix.len() + data.len()).map_err(|_| Error::BufferNotEnough)?;
+        let header = SubMessageHeader::WriteData(len, DataFormat::FormatData);
+        if self.needs_fragmentation(stream_id, header) {
+            self.write_fragmented(stream_id, header, prefix, data)?;
+        } else {
+            let buf = self.prepare_output_stream(stream_id, header)?;
+            copy_parts(buf, &[prefix, data], 0);
+        }
+        Ok(request_id)
+    }
+
+    /// Returns true when the submessage is too long for a single message of a
+    /// reliable output stream, or for the MTU of the session.
+    fn needs_fragmentation(&mut self, stream_id: StreamId, header: SubMessageHeader) -> bool {
+        if stream_id.direction != StreamDirection::OutputStream
+            || stream_id.type_u != StreamType::ReliableStream
+        {
+            return false;
+        }
+
+        self.output_stream_message_len(stream_id)
+            .is_ok_and(|len| SUBHEADER_SIZE + header.payload_len() as usize > len)
+    }
+
+    ///
+    /// Splits the submessage made of `header`, `prefix` and `data` into FRAGMENT
+    /// submessages, each one sent in its own message of the reliable output stream.
+    /// The messages are not longer than the MTU of the session, the agent joins the
+    /// fragments back together once the last one is received.
+    ///
+    /// +-----------+-----------------+   +-----------+-----------------+
+    /// | FRAGMENT  | header, prefix, | . | FRAGMENT  | ..., data       |
+    /// |           | data, ...       |   | (last)    |                 |
+    /// +-----------+-----------------+   +-----------+-----------------+
+    ///
+    fn write_fragmented(
+        &mut self,
+        stream_id: StreamId,
+        header: SubMessageHeader,
+        prefix: &[u8],
+        data: &[u8],
+    ) -> SessionResult<()> {
+        let mut subheader = [0u8; SUBHEADER_SIZE];
+        header
+            .to_slice(&mut subheader)
+            .map_err(|_| Error::BufferNotEnough)?;
+        let parts = [&subheader[..], prefix, data];
+        let total_len = parts.iter().map(|part| part.len()).sum::<usize>();
+
+        let fragment_len = self
+            .output_stream_message_len(stream_id)?
+            .saturating_sub(SUBHEADER_SIZE);
+        let stream = self
+            .streams
+            .get_output_reliable(stream_id.index)
+            .ok_or(Error::InvalidStream)?;
+        if fragment_len == 0 {
+            return Err(Error::BufferNotEnough);
+        }
+
+        let fragments = total_len.div_ceil(fragment_len);
+        if !u16::try_from(fragments).is_ok_and(|fragments| stream.has_free_slots(fragments)) {
+            return Err(Error::BufferNotEnough);
+        }
+
+        for index in 0..fragments {
+            let begin = index * fragment_len;
+            let len = fragment_len.min(total_len - begin);
+            let buf = stream
+                .prepare_new_message(SUBHEADER_SIZE + len)
+                .ok_or(Error::BufferNotEnough)?;
+
+            SubMessageHeader::Fragment(len as u16, index + 1 == fragments)
+                .to_slice(&mut buf[..SUBHEADER_SIZE])
+                .map_err(|_| Error::BufferNotEnough)?;
+            copy_parts(&mut buf[SUBHEADER_SIZE..], &parts, begin);
+        }
+
+        Ok(())
+    }
+
+    fn write_create(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        object_representation: ObjectVariant,
+        mode: CreationMode,
+    ) -> SessionResult<u16> {
+        if !object_representation.has_valid_representation() {
+            return Err(Error::InvalidUsage);
+        }
+
+        let request_id = self.info.generate_request_id();
+        let payload = CREATE_Payload {
+            base: BaseObjectRequest {
+                request_id: request_id.to_be_bytes(),
+                object_id,
+            },
+            object_representation,
+        };
+
+        let len = u16::try_from(payload.size()).map_err(|_| Error::BufferNotEnough)?;
+        let buf = self.prepare_output_stream(
+            stream_id,
+            SubMessageHeader::Create(len, mode.replace, mode.reuse),
+        )?;
+        payload.to_slice(buf).map_err(|_| Error::BufferNotEnough)?;
+
+        if self.supervisor.is_enabled() {
+            let parent_id = payload.object_representation.parent_id();
+            match self
+                .supervisor
+                .record(stream_id, object_id, parent_id, mode, len as usize)
+            {
+                Some(buf) => {
+                    payload.to_slice(buf).map_err(|_| Error::BufferNotEnough)?;
+                }
+                None => {
+                    // an entity that could not be replayed is not created either
+                    self.truncate_output_stream(stream_id, SUBHEADER_SIZE + len as usize);
+                    return Err(Error::OutOfResources);
+                }
+            }
+        }
+
+        self.status_table.track(request_id);
+        Ok(request_id)
+    }
+
+    /// Writes a DELETE for the entity `object_id` into `stream_id`, returns the
+    /// request id the agent will answer with a STATUS.
+    pub fn delete_entity(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+    ) -> SessionResult<u16> {
+        let request_id = self.info.generate_request_id();
+        let base = BaseObjectRequest {
+            request_id: request_id.to_be_bytes(),
+            object_id,
+        };
+
+        let buf = self.prepare_output_stream(
+            stream_id,
+            SubMessageHeader::Delete(BASE_OBJECT_REQUEST_SIZE as u16),
+        )?;
+        base.serialize(&mut micro_cdr::Encoder::new(buf))
+            .map_err(|_| Error::BufferNotEnough)?;
+        self.status_table.track(request_id);
+        self.supervisor.remove(object_id);
+        Ok(request_id)
+    }
+
+    /// Status of the CREATE or DELETE with the given `request_id`, none when it is
+    /// not one of the last requests issued by the session.
+    pub fn request_status(&self, request_id: u16) -> Option<RequestStatus> {
+        self.status_table.get(request_id)
+    }
+
+    /// Sends every message pending in the output streams, and a HEARTBEAT for each
+    /// reliable output stream whose messages are waiting to be acknowledged.
+    pub fn flush_output_streams(&mut self) -> SessionResult<()> {
+        for (index, stream) in self.streams.output_best_effort.iter_mut().enumerate() {
+            if let Some((buf, seq_num)) = stream
+                .as_mut()
+                .and_then(|stream| stream.prepare_stream_to_send())
+            {
+                let id = StreamId::new(
+                    index as u8,
+                    StreamType::BestEffortStream,
+                    StreamDirection::OutputStream,
+                );
+                self.info.stamp_session_header(id.raw, seq_num, buf);
+                self.transport.send_msg(buf)?;
+            }
+        }
+
+        for (index, stream) in self.streams.output_reliable.iter_mut().enumerate() {
+            if let Some(stream) = stream {
+                let id = StreamId::new(
+                    index as u8,
+                    StreamType::ReliableStream,
+                    StreamDirection::OutputStream,
+                );
+                while let Some((buf, seq_num)) = stream.prepare_next_to_send() {
+                    self.info.stamp_session_header(id.raw, seq_num, buf);
+                    self.transport.send_msg(buf)?;
+                }
+            }
+        }
+
+        self.send_heartbeats()
+    }
+
+    /// Sends the pending messages and processes the ones received until `timeout`
+    /// milliseconds have elapsed. Returns true when all the reliable output has been
+    /// acknowledged by then.
+    pub fn run_until_timeout(&mut self, timeout: i32) -> SessionResult<bool> {
+        self.run_until(timeout, |_| false)?;
+        Ok(self.output_streams_confirmed())
+    }
+
+    /// Sends the pending messages and processes the ones received until all the
+    /// reliable output has been acknowledged or `timeout` milliseconds have elapsed.
+    /// Returns true when the output was acknowledged.
+    pub fn run_until_confirm_delivery(&mut self, timeout: i32) -> SessionResult<bool> {
+        self.run_until(timeout, |session| session.output_streams_confirmed())
+    }
+
+    /// Sends the pending messages and processes the ones received until there is a
+    /// STATUS for each one of `request_ids` or `timeout` milliseconds have elapsed.
+    /// Returns true when all of them were received and successful, their details are
+    /// given by [`Session::request_status`].
+    pub fn run_until_all_status(
+        &mut self,
+        timeout: i32,
+        request_ids: &[u16],
+    ) -> SessionResult<bool> {
+        let received = self.run_until(timeout, |session| {
+            request_ids.iter().all(|&request_id| {
+                matches!(
+                    session.request_status(request_id),
+                    Some(RequestStatus::Received(_))
+                )
+            })
+        })?;
+
+        Ok(received
+            && request_ids.iter().all(|&request_id| {
+                matches!(
+                    self.request_status(request_id),
+                    Some(RequestStatus::Received(status)) if status.is_ok()
+                )
+            }))
+    }
+
+    /// Flushes the output streams and listens until `done` or until `timeout`
+    /// milliseconds have elapsed, returns whether `done` was reached.
+    fn run_until<F>(&mut self, timeout: i32, done: F) -> SessionResult<bool>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        self.flush_output_streams()?;
+
+        let start_timestamp = self.transport.now();
+        let mut remaining_time = timeout;
+        while remaining_time > 0 && !done(self) {
+            self.listen_message_reliably(remaining_time)?;
+            remaining_time = timeout - self.transport.now().wrapping_sub(start_timestamp);
+        }
+
+        Ok(done(self))
+    }
+
+    /// Sends a GET_INFO to the agent up to `attempts` times, waiting `timeout`
+    /// milliseconds for its INFO after each one. It does not need the session to be
+    /// created, so it can be used to look for the agent before and to check that it is
+    /// still alive after. Returns true when the agent answered that it is available.
+    pub fn ping_agent(&mut self, timeout: i32, attempts: usize) -> SessionResult<bool> {
+        let mut get_info_buffer = [0u8; GET_INFO_MAX_MSG_SIZE];
+
+        // sent outside of the session, as a CREATE_CLIENT
+        let session_id = self.info.id & SESSION_ID_WITHOUT_CLIENT_KEY;
+        let key = if SESSION_ID_WITHOUT_CLIENT_KEY > session_id {
+            Some(self.info.key)
+        } else {
+            None
+        };
+        let len1 = MessageHeader::new(session_id, 0, 0, key)
+            .to_slice(&mut get_info_buffer)
+            .unwrap();
+
+        let payload = GET_INFO_Payload {
+            base: BaseObjectRequest {
+                request_id: REQUEST_GET_INFO,
+                object_id: OBJECTID_AGENT,
+            },
+            info_mask: INFO_CONFIGURATION | INFO_ACTIVITY,
+        };
+        let len2 = payload.to_slice(&mut get_info_buffer[len1..]).unwrap();
+
+        self.agent_availability = None;
+        for _ in 0..attempts {
+            self.transport.send_msg(&get_info_buffer[..len1 + len2])?;
+
+            let start_timestamp = self.transport.now();
+            let mut remaining_time = timeout;
+            while remaining_time > 0 && self.agent_availability.is_none() {
+                self.listen_message_reliably(remaining_time)?;
+                remaining_time = timeout - self.transport.now().wrapping_sub(start_timestamp);
+            }
+
+            if self.agent_availability.is_some() {
+                break;
+            }
+        }
+
+        Ok(self
+            .agent_availability
+            .is_some_and(|availability| availability > 0))
+    }
+
+    /// Starts supervising the agent with [`Session::supervise`]: the session is lost
+    /// after `max_failed_pings` pings left unanswered in a row or a RESET from the
+    /// agent. From now on, the CREATE of every entity is kept in `buffer` to be replayed
+    /// once the session is created again, until the entity is deleted.
+    ///
+    /// Once `buffer` is full or the most entities that can be kept are, creating an
+    /// entity fails with [`Error::OutOfResources`] and its CREATE is not written.
+    pub fn enable_supervision(
+        &mut self,
+        buffer: &'storage mut [u8],
+        max_failed_pings: usize,
+    ) -> SessionResult<()> {
+        if max_failed_pings == 0 {
+            return Err(Error::InvalidUsage);
+        }
+
+        self.supervisor.enable(buffer, max_failed_pings);
+        Ok(())
+    }
+
+    /// Pings the agent, waiting at most `timeout` milliseconds for its answer, to be
+    /// called periodically once the supervision is enabled. When the agent answers
+    /// after the session was lost, the session is created again and the entities kept
+    /// are replayed, waiting at most `timeout` milliseconds for their STATUS.
+    pub fn supervise(&mut self, timeout: i32) -> SessionResult<AgentState> {
+        if !self.supervisor.is_enabled() {
+            return Err(Error::InvalidUsage);
+        }
+
+        if !self.ping_agent(timeout, 1)? {
+            return Ok(AgentState::Unreachable(self.supervisor.ping_failed()));
+        }
+
+        if !self.supervisor.ping_answered() {
+            return Ok(AgentState::Alive);
+        }
+
+        self.create()?;
+        let supervisor = core::mem::take(&mut self.supervisor);
+        let replayed = self.replay_creations(&supervisor, timeout);
+        self.supervisor = supervisor;
+
+        let confirmed = replayed?;
+        self.supervisor.reestablished();
+        Ok(AgentState::Reestablished(confirmed))
+    }
+
+    /// Writes again the CREATE of the entities kept by `supervisor`, with new request
+    /// ids, and waits for their STATUS. Returns true when all of them were successful.
+    fn replay_creations(&mut self, supervisor: &Supervisor, timeout: i32) -> SessionResult<bool> {
+        let mut request_ids = [0u16; MAX_SUPERVISED_ENTITIES];
+        let mut count = 0;
+
+        for (creation, payload) in supervisor.creations() {
+            let request_id = self.info.generate_request_id();
+            // the stream may be full of the creations written before
+            match self.write_creation(creation, payload, request_id) {
+                Err(Error::BufferNotEnough) => {
+                    self.run_until_confirm_delivery(timeout)?;
+                    self.write_creation(creation, payload, request_id)?;
+                }
+                result => result?,
+            }
+
+            self.status_table.track(request_id);
+            request_ids[count] = request_id;
+            count += 1;
+        }
+
+        self.run_until_all_status(timeout, &request_ids[..count])
+    }
+
+    fn write_creation(
+        &mut self,
+        creation: &Creation,
+        payload: &[u8],
+        request_id: u16,
+    ) -> SessionResult<()> {
+        let buf = self.prepare_output_stream(
+            creation.stream_id,
+            SubMessageHeader::Create(
+                payload.len() as u16,
+                creation.mode.replace,
+                creation.mode.reuse,
+            ),
+        )?;
+        buf.copy_from_slice(payload);
+        // the request id begins the payload
+        buf[..2].copy_from_slice(&request_id.to_be_bytes());
+        Ok(())
+    }
+
+    /// Registers `handler` to be called with every INFO received from the agent.
+    pub fn set_info_handler(&mut self, handler: InfoHandler<'storage>) {
+        self.info_handler.0 = Some(handler);
+    }
+
+    /// Sends a TIMESTAMP to the agent and waits at most `timeout` milliseconds for its
+    /// reply, from which the offset between the clocks is computed as done by NTP.
+    /// Returns true when the session was synchronized.
+    pub fn sync_time(&mut self, timeout: i32) -> SessionResult<bool> {
+        let mut timestamp_buffer = [0u8; TIMESTAMP_MAX_MSG_SIZE];
+        let len1 = self
+            .info
+            .stamp_session_header(0, SeqNum::new(0), &mut timestamp_buffer);
+
+        let payload = TIMESTAMP_Payload {
+            transmit_timestamp: Time::from_nanos(self.transport.now_nanos()),
+        };
+        let len2 = payload.to_slice(&mut timestamp_buffer[len1..]).unwrap();
+
+        self.synchronized = false;
+        self.sent_timestamp = Some(payload.transmit_timestamp);
+        self.transport.send_msg(&timestamp_buffer[..len1 + len2])?;
+        self.run_until(timeout, |session| session.synchronized)
+    }
+
+    /// Milliseconds since the epoch of the agent clock, the local clock is used
+    /// as is until the session is synchronized.
+    pub fn epoch_millis(&mut self) -> i64 {
+        self.transport.now() as i64 - self.time_offset / 1_000_000
+    }
+
+    /// Nanoseconds since the epoch of the agent clock, the local clock is used
+    /// as is until the session is synchronized.
+    pub fn epoch_nanos(&mut self) -> i64 {
+        self.transport.now_nanos() - self.time_offset
+    }
+
+    /// Round-trip delay of the last synchronization, without the time the agent took
+    /// to reply, in nanoseconds.
+    pub fn round_trip_nanos(&self) -> i64 {
+        self.round_trip
+    }
+
+    /// Vendor id of the agent which replied to the last CREATE_CLIENT, None until one
+    /// has replied.
+    pub fn agent_vendor_id(&self) -> Option<[u8; 2]> {
+        self.info.agent_vendor_id
+    }
+
+    /// Returns true when every message of the reliable output streams has been
+    /// acknowledged.
+    pub fn output_streams_confirmed(&self) -> bool {
+        self.streams
+            .output_reliable
+            .iter()
+            .flatten()
+            .all(|stream| stream.is_up_to_date())
+    }
+
+    /// Listens for a message during at most `timeout` milliseconds, waking up to send
+    /// the HEARTBEATs of the reliable output streams when they are due.
+    fn listen_message_reliably(&mut self, timeout: i32) -> SessionResult<bool> {
+        let mut remaining_time = timeout;
+        loop {
+            self.send_heartbeats()?;
+
+            let now = self.transport.now() as i64;
+            let next_heartbeat = self
+                .streams
+                .output_reliable
+                .iter()
+                .flatten()
+                .map(|stream| stream.next_heartbeat_timestamp())
+                .min()
+                .unwrap_or(i64::MAX);
+            let poll = if next_heartbeat == i64::MAX {
+                remaining_time
+            } else {
+                (next_heartbeat - now).clamp(1, remaining_time as i64) as i32
+            };
+
+            if self.listen_message(poll)? {
+                return Ok(true);
+            }
+
+            remaining_time -= poll;
+            if remaining_time <= 0 {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn send_heartbeats(&mut self) -> SessionResult<()> {
+        let now = self.transport.now() as i64;
+
+        for (index, stream) in self.streams.output_reliable.iter_mut().enumerate() {
+            if let Some(stream) = stream {
+                if stream.update_heartbeat_timestamp(now) {
+                    let id = StreamId::new(
+                        index as u8,
+                        StreamType::ReliableStream,
+                        StreamDirection::OutputStream,
+                    );
+                    let payload = HEARTBEAT_Payload {
+                        first_unacked_seq_nr: stream.first_unacked_seq_num().get(),
+                        last_unacked_seq_nr: stream.last_sent().get(),
+                        stream_id: id.raw,
+                    };
+
+                    let mut heartbeat_buffer = [0u8; HEARTBEAT_MAX_MSG_SIZE];
+                    let len1 =
+                        self.info
+                            .stamp_session_header(0, SeqNum::new(0), &mut heartbeat_buffer);
+                    let len2 = payload.to_slice(&mut heartbeat_buffer[len1..]).unwrap();
+                    self.transport.send_msg(&heartbeat_buffer[..len1 + len2])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn buffer_create_session(&self, mtu: u16, buf: &mut [u8]) -> error::Result<usize> {
         let payload = CREATE_CLIENT_Payload(CLIENT_Representation {
-            xrce_cookie: [b'X', b'R', b'C', b'E'],
-            xrce_version: [0x01u8, 0x00u8],
-            xrce_vendor_id: [0x01, 0x0F],
+            xrce_cookie: XRCE_COOKIE,
+            xrce_version: [XRCE_VERSION_MAJOR, XRCE_VERSION_MINOR],
+            xrce_vendor_id: XRCE_VENDOR_ID,
             client_key: self.info.key,
             session_id: self.info.id,
 
@@ -143,35 +1355,70 @@ impl<'storage, 'a: 'storage, T: Transmitter + Receiver<'a> + Clock> Session<'sto
     }
 
     fn wait_session_status(&mut self, buf: &[u8], attempts: usize) -> SessionResult<()> {
+        self.info.last_requested_status = None;
+
         if attempts == 0 {
-            self.transport.send_msg(buf).unwrap();
+            self.transport.send_msg(buf)?;
             return Ok(());
         }
 
         for _ in 0..attempts {
-            self.transport.send_msg(buf).unwrap();
+            self.transport.send_msg(buf)?;
 
             let start_timestamp = self.transport.now();
-            let remaining_time = MIN_SESSION_CONNECTION_INTERVAL;
+            let mut remaining_time = MIN_SESSION_CONNECTION_INTERVAL;
+            while remaining_time > 0 && self.info.last_requested_status.is_none() {
+                self.listen_message(remaining_time as i32)?;
+                remaining_time = MIN_SESSION_CONNECTION_INTERVAL
+                    - self.transport.now().wrapping_sub(start_timestamp) as i64;
+            }
+
+            if self.info.last_requested_status.is_some() {
+                break;
+            }
         }
 
-        Ok(())
+        match self.info.last_requested_status {
+            Some(status) => status_to_result(status),
+            None => Err(Error::Timeout),
+        }
+    }
+
+    fn listen_message(&mut self, timeout: i32) -> SessionResult<bool> {
+        let mut buf = [0u8; MAX_TRANSPORT_MTU];
+
+        let len = match self.transport.receive_msg(timeout) {
+            Ok(recv) if recv.len() <= MAX_TRANSPORT_MTU => {
+                buf[..recv.len()].copy_from_slice(recv);
+                recv.len()
+            }
+            Ok(_) => return Ok(false),
+            Err(Error::Timeout) | Err(Error::RemoteAddrError) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        if len == 0 {
+            return Ok(false);
+        }
+
+        // messages addressed to other sessions or malformed ones are dropped
+        Ok(self.read_message(&buf[..len]).is_ok())
     }
 
-    fn read_message(&self, buf: &[u8]) -> SessionResult<()> {
+    fn read_message(&mut self, buf: &[u8]) -> SessionResult<()> {
         let header = MessageHeader::from_slice(buf).map_err(|_| Error::InvalidData)?;
 
+        // the replies to the messages sent outside of the session, such as GET_INFO,
+        // which are never part of a stream
+        let sessionless = header.session_id != self.info.id
+            && header.session_id == self.info.id & SESSION_ID_WITHOUT_CLIENT_KEY
+            && header.stream_id == 0;
+
         let mut correct_msg: bool = false;
-        if header.session_id == self.info.id {
+        if header.session_id == self.info.id || sessionless {
             if SESSION_ID_WITHOUT_CLIENT_KEY > self.info.id {
                 if let Some(key) = header.key {
-                    if key == self.info.key {
-                        correct_msg = true;
-                    } else {
-                        correct_msg = false;
-                    }
-                } else {
-                    correct_msg = false;
+                    correct_msg = key == self.info.key;
                 }
             } else {
                 correct_msg = true;
@@ -179,86 +1426,322 @@ impl<'storage, 'a: 'storage, T: Transmitter + Receiver<'a> + Clock> Session<'sto
         };
 
         if correct_msg {
-            let id = StreamId::from_raw(
-                header.session_id,
-                crate::stream_id::StreamDirection::InputStream,
-            );
+            let id = StreamId::from_raw(header.stream_id, StreamDirection::InputStream);
             if header.key.is_some() {
-                self.read_stream(&buf[(MAX_HEADER_SIZE - 1)..], id, header.sequence_num);
+                self.read_stream(&buf[MAX_HEADER_SIZE..], id, header.sequence_num)
             } else {
-                self.read_stream(&buf[(MIN_HEADER_SIZE - 1)..], id, header.sequence_num);
+                self.read_stream(&buf[MIN_HEADER_SIZE..], id, header.sequence_num)
             }
-            Ok(())
         } else {
             Err(Error::InvalidData)
         }
     }
 
-    fn read_stream(&self, buf: &[u8], stream_id: StreamId, seq_num: u16) {
+    fn read_stream(&mut self, buf: &[u8], stream_id: StreamId, seq_num: SeqNum) -> Result<()> {
         match stream_id.type_u {
-            stream_id::StreamType::NoneStream => {}
-            stream_id::StreamType::BestEffortStream => {}
-            stream_id::StreamType::ReliableStream => {}
-            stream_id::StreamType::SharedMemoryStream => {
+            StreamType::NoneStream => self.read_submessage_list(buf, stream_id),
+            StreamType::BestEffortStream => {
+                let newer = match self.streams.get_input_best_effort(stream_id.index) {
+                    Some(stream) => stream.receive_message(seq_num),
+                    None => return Ok(()),
+                };
+
+                if newer {
+                    self.read_submessage_list(buf, stream_id)
+                } else {
+                    Ok(())
+                }
+            }
+            StreamType::ReliableStream => {
+                let ready = match self.streams.get_input_reliable(stream_id.index) {
+                    Some(stream) => stream.receive_message(seq_num, buf),
+                    None => return Ok(()),
+                };
+
+                let mut result = Ok(());
+                if ready {
+                    result = self.read_submessage_list(buf, stream_id);
+                    result = result.and(self.read_stored_messages(stream_id));
+                }
+
+                // a message that can't be read is acknowledged all the same, the agent
+                // would keep sending it otherwise
+                self.write_submessage_acknack(stream_id)?;
+                result
+            }
+            StreamType::SharedMemoryStream => {
                 unimplemented!()
             }
+        }
+    }
+
+    /// Reads the messages stored out of order which follow the last handled one.
+    fn read_stored_messages(&mut self, stream_id: StreamId) -> Result<()> {
+        let mut result = Ok(());
+        let mut next_message = [0u8; MAX_TRANSPORT_MTU];
+        while let Some(len) = self
+            .streams
+            .get_input_reliable(stream_id.index)
+            .and_then(|stream| stream.next_message_available(&mut next_message))
+        {
+            let read = self.read_submessage_list(&next_message[..len], stream_id);
+            result = result.and(read);
+        }
+        result
+    }
+
+    fn read_submessage_list(&mut self, buf: &[u8], stream_id: StreamId) -> Result<()> {
+        for submessage in SubMessages::new(buf) {
+            let submessage = submessage.map_err(|_| Error::InvalidData)?;
+            let (payload, endianness) = (submessage.payload, submessage.endianness());
+            match submessage.header {
+                SubMessageHeader::StatusAgent(_) => {
+                    self.read_submessage_status_agent(payload, endianness, &stream_id)
+                }
+                SubMessageHeader::Status(_) => self.read_submessage_status(payload, endianness),
+                SubMessageHeader::AckNack(_) => {
+                    self.read_submessage_acknack(payload, endianness)?
+                }
+                SubMessageHeader::HeartBeat(_) => {
+                    self.read_submessage_heartbeat(payload, endianness)?
+                }
+                SubMessageHeader::Data(_, format) => {
+                    self.read_submessage_data(payload, format, endianness, stream_id)
+                }
+                SubMessageHeader::Info(_) => self.read_submessage_info(payload, endianness),
+                SubMessageHeader::TimeStampReply(_) => {
+                    self.read_submessage_timestamp_reply(payload, endianness)
+                }
+                SubMessageHeader::Fragment(_, last) => {
+                    self.read_submessage_fragment(payload, last, stream_id)?
+                }
+                SubMessageHeader::Reset(_) => self.supervisor.agent_reset(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn read_submessage_status_agent(
+        &mut self,
+        payload: &[u8],
+        endianness: Endianness,
+        stream_id: &StreamId,
+    ) {
+        if let StreamType::NoneStream = stream_id.type_u {
+            if let Ok(status) = from_payload::<STATUS_AGENT_Payload>(payload, endianness) {
+                if status.agent_info.xrce_cookie != XRCE_COOKIE {
+                    return;
+                }
+
+                self.info.agent_vendor_id = Some(status.agent_info.xrce_vendor_id);
+                self.info.last_requested_status =
+                    if status.agent_info.xrce_version[0] != XRCE_VERSION_MAJOR {
+                        Some(STATUS_ERR_INCOMPATIBLE)
+                    } else {
+                        Some(status.result.status)
+                    };
+            }
+        }
+    }
+
+    fn read_submessage_status(&mut self, payload: &[u8], endianness: Endianness) {
+        if let Ok(status) = from_payload::<STATUS_Payload>(payload, endianness) {
+            if status.related_request.request_id == REQUEST_LOGOUT {
+                self.info.last_requested_status = Some(status.result.status);
+            } else {
+                self.status_table.record(Status {
+                    object_id: status.related_request.object_id,
+                    request_id: u16::from_be_bytes(status.related_request.request_id),
+                    status: status.result.status,
+                    implementation_status: status.result.implementation_status,
+                });
+            }
+        }
+    }
+
+    fn read_submessage_data(
+        &mut self,
+        payload: &[u8],
+        format: DataFormat,
+        endianness: Endianness,
+        stream_id: StreamId,
+    ) {
+        // the samples of a malformed payload are dropped from the first invalid one,
+        // those before it have already been delivered
+        let _ = self
+            .data_handlers
+            .dispatch(stream_id, format, endianness, payload);
+    }
+
+    fn read_submessage_info(&mut self, payload: &[u8], endianness: Endianness) {
+        if let Ok(agent_info) = info::from_payload(payload, endianness) {
+            if let Some(availability) = agent_info.availability {
+                self.agent_availability = Some(availability);
+            }
+            if let Some(handler) = self.info_handler.0.as_mut() {
+                handler(&agent_info);
+            }
+        }
+    }
+
+    fn read_submessage_timestamp_reply(&mut self, payload: &[u8], endianness: Endianness) {
+        if let Ok(reply) = from_payload::<TIMESTAMP_REPLY_Payload>(payload, endianness) {
+            // the late replies to the previous TIMESTAMP are ignored
+            if self.sent_timestamp != Some(reply.originate_timestamp) {
+                return;
+            }
+
+            self.sent_timestamp = None;
+            let t0 = reply.originate_timestamp.to_nanos();
+            let t1 = reply.receive_timestamp.to_nanos();
+            let t2 = reply.transmit_timestamp.to_nanos();
+            let t3 = self.transport.now_nanos();
+
+            self.time_offset = ((t0 + t3) - (t1 + t2)) / 2;
+            self.round_trip = (t3 - t0) - (t2 - t1);
+            self.synchronized = true;
+        }
+    }
+
+    fn read_submessage_fragment(
+        &mut self,
+        payload: &[u8],
+        last: bool,
+        stream_id: StreamId,
+    ) -> Result<()> {
+        if stream_id.type_u != StreamType::ReliableStream {
+            return Ok(());
+        }
+
+        let (buffer, len) = match self
+            .streams
+            .get_input_reliable(stream_id.index)
+            .and_then(|stream| stream.push_fragment(payload, last))
+        {
+            Some(reassembled) => reassembled,
+            None => return Ok(()),
         };
+
+        // the fragments received while reading it are dropped, as the buffer is in use
+        let result = self.read_submessage_list(&buffer[..len], stream_id);
+        if let Some(stream) = self.streams.get_input_reliable(stream_id.index) {
+            stream.set_reassembly_buffer(buffer);
+        }
+        result
     }
 
-    fn read_submessage_list(buf: &[u8], stream_id: StreamId) -> Result<()> {
-        let mut pos = 0;
-        while let Ok(submessage_hdr) = submessage::SubMessageHeader::from_slice(&buf[pos..]) {
-            match submessage_hdr {
-                SubMessageHeader::Data(len, format) => {
+    fn read_submessage_acknack(&mut self, payload: &[u8], endianness: Endianness) -> Result<()> {
+        let acknack: ACKNACK_Payload =
+            from_payload(payload, endianness).map_err(|_| Error::InvalidData)?;
+        let id = StreamId::from_raw(acknack.stream_id, StreamDirection::OutputStream);
+        if id.type_u != StreamType::ReliableStream {
+            return Ok(());
+        }
+
+        if let Some(stream) = self.streams.get_output_reliable(id.index) {
+            let nack_bitmap = u16::from_be_bytes(acknack.nack_bitmap);
+            let first_unacked_seq_num = SeqNum::new(acknack.first_unacked_seq_num);
+            let lost = stream.process_acknack(nack_bitmap, first_unacked_seq_num);
 
+            for i in 0..16 {
+                if lost & (1 << i) != 0 {
+                    let seq_num = first_unacked_seq_num.add(i);
+                    let buf = stream.get_message(seq_num);
+                    self.info.stamp_session_header(id.raw, seq_num, buf);
+                    self.transport.send_msg(buf)?;
                 }
-                _ => { unimplemented!() }
             }
         }
+
         Ok(())
     }
 
-    fn listen_message(&'storage mut self, remaining_time: i32) -> Result<()> {
-        let recv = self.transport.receive_msg(remaining_time)?;
-
-        if recv.len() != 0 {
-            // self.
-            // let header = MessageHeader::from_slice(recv).map_err(|_| Error::InvalidData)?;
-            // if header.session_id == self.info.id {
-            //     if let Some(key) = header.key {
-            //         if key == self.info.key {
-            //             let id = StreamId::from_raw(
-            //                 header.session_id,
-            //                 StreamDirection::InputStream,
-            //             );
-            //             match id.type_u {
-            //                 StreamType::NoneStream => {
-            //                     let stream_id = StreamId::from_raw(
-            //                         0x00, StreamDirection::InputStream);
-            //                     if header.key.is_some() {
-            //                         Self::read_submessage_list(&recv[(MAX_HEADER_SIZE - 1)..], stream_id)?;
-            //                     } else {
-            //                         Self::read_submessage_list(&recv[(MIN_HEADER_SIZE - 1)..], stream_id)?;
-            //                     }
-            //                 },
-            //                 StreamType::BestEffortStream => {
-            //                     unimplemented!()
-            //                 },
-            //                 StreamType::ReliableStream => {
-            //                     unimplemented!()
-            //                 },
-            //                 StreamType::SharedMemoryStream => {
-            //                     unimplemented!()
-            //                 }
-            //             };
-            //         } else {
-            //             return Err(Error::InvalidData);
-            //         }
-            //     }
-            // } else {
-            //     return Err(Error::InvalidData);
-            // }
+    fn read_submessage_heartbeat(&mut self, payload: &[u8], endianness: Endianness) -> Result<()> {
+        let heartbeat: HEARTBEAT_Payload =
+            from_payload(payload, endianness).map_err(|_| Error::InvalidData)?;
+        let id = StreamId::from_raw(heartbeat.stream_id, StreamDirection::InputStream);
+        if id.type_u != StreamType::ReliableStream {
+            return Ok(());
+        }
+
+        match self.streams.get_input_reliable(id.index) {
+            Some(stream) => stream.process_heartbeat(
+                heartbeat.first_unacked_seq_nr.into(),
+                heartbeat.last_unacked_seq_nr.into(),
+            ),
+            None => return Ok(()),
         }
+
+        // the skipped messages may leave the stored ones next in order
+        let result = self.read_stored_messages(id);
+        self.write_submessage_acknack(id)?;
+        result
+    }
+
+    fn write_submessage_acknack(&mut self, stream_id: StreamId) -> Result<()> {
+        let (first_unacked_seq_num, nack_bitmap) =
+            match self.streams.get_input_reliable(stream_id.index) {
+                Some(stream) => stream.compute_acknack(),
+                None => return Ok(()),
+            };
+
+        let payload = ACKNACK_Payload {
+            first_unacked_seq_num: first_unacked_seq_num.get(),
+            nack_bitmap: nack_bitmap.to_be_bytes(),
+            stream_id: stream_id.raw,
+        };
+
+        let mut acknack_buffer = [0u8; ACKNACK_MAX_MSG_SIZE];
+        let len1 = self
+            .info
+            .stamp_session_header(0, SeqNum::new(0), &mut acknack_buffer);
+        let len2 = payload.to_slice(&mut acknack_buffer[len1..]).unwrap();
+        self.transport.send_msg(&acknack_buffer[..len1 + len2])?;
         Ok(())
     }
 }
+
+/// Writes the WRITE_DATA base followed by `data`, which is aligned from its own
+/// beginning as the readers of the topic deserialize it.
+fn serialize_sample<D: Serialize + ?Sized>(
+    buf: &mut [u8],
+    base: &BaseObjectRequest,
+    data: &D,
+) -> error::Result<usize> {
+    let mut ucdr = micro_cdr::Encoder::new(buf);
+    base.serialize(&mut ucdr)?;
+    let base_len = ucdr.finalize();
+
+    let mut ucdr = micro_cdr::Encoder::new(&mut buf[base_len..]);
+    data.serialize(&mut ucdr)?;
+    Ok(base_len + ucdr.finalize())
+}
+
+/// Fills `buf` with the bytes of the concatenation of `parts` starting at `offset`.
+pub(crate) fn copy_parts(buf: &mut [u8], parts: &[&[u8]], mut offset: usize) {
+    let mut written = 0;
+    for part in parts {
+        if offset >= part.len() {
+            offset -= part.len();
+            continue;
+        }
+
+        let len = (part.len() - offset).min(buf.len() - written);
+        buf[written..written + len].copy_from_slice(&part[offset..offset + len]);
+        written += len;
+        offset = 0;
+        if written == buf.len() {
+            break;
+        }
+    }
+}
+
+/// Decodes a received submessage payload encoded with `endianness`.
+fn from_payload<'de, T: Deserialize<'de>>(
+    payload: &'de [u8],
+    endianness: Endianness,
+) -> error::Result<T> {
+    let mut ucdr = micro_cdr::Decoder::new_with_endianness(payload, endianness);
+    T::deserialize(&mut ucdr)
+}
@@ -0,0 +1,115 @@
+use crate::object_id::ObjectId;
+use crate::{Error, Result, MAX_STATUS_REQUESTS};
+
+pub use crate::types::{
+    STATUS_ERR_ALREADY_EXISTS, STATUS_ERR_DDS_ERROR, STATUS_ERR_DENIED, STATUS_ERR_INCOMPATIBLE,
+    STATUS_ERR_INVALID_DATA, STATUS_ERR_MISMATCH, STATUS_ERR_NOVALUE, STATUS_ERR_RESOURCES,
+    STATUS_ERR_UNKNOWN_REFERENCE, STATUS_OK, STATUS_OK_MATCHED,
+};
+
+///
+/// Result of a request, as reported by the STATUS the agent answers it with.
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |            requestId            |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |     status     | implementation |
+/// +----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Status {
+    pub object_id: ObjectId,
+    pub request_id: u16,
+    pub status: u8,
+    pub implementation_status: u8,
+}
+
+impl Status {
+    pub fn is_ok(&self) -> bool {
+        self.result().is_ok()
+    }
+
+    pub fn result(&self) -> Result<()> {
+        status_to_result(self.status)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RequestStatus {
+    /// The request was issued but the agent has not answered it yet.
+    Pending,
+    Received(Status),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    request_id: u16,
+    status: RequestStatus,
+}
+
+///
+/// Status of the last requests issued by the session. Once full, tracking a new
+/// request forgets the oldest one.
+///
+#[derive(Debug, Default)]
+pub(crate) struct StatusTable {
+    entries: [Option<Entry>; MAX_STATUS_REQUESTS],
+    next: usize,
+}
+
+impl StatusTable {
+    pub(crate) fn track(&mut self, request_id: u16) {
+        let index = match self.position(request_id) {
+            Some(index) => index,
+            None => {
+                let index = self.next;
+                self.next = (self.next + 1) % MAX_STATUS_REQUESTS;
+                index
+            }
+        };
+
+        self.entries[index] = Some(Entry {
+            request_id,
+            status: RequestStatus::Pending,
+        });
+    }
+
+    /// Records the status of a tracked request, returns false when the request is
+    /// not tracked.
+    pub(crate) fn record(&mut self, status: Status) -> bool {
+        match self.position(status.request_id) {
+            Some(index) => {
+                self.entries[index] = Some(Entry {
+                    request_id: status.request_id,
+                    status: RequestStatus::Received(status),
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn get(&self, request_id: u16) -> Option<RequestStatus> {
+        self.position(request_id)
+            .and_then(|index| self.entries[index])
+            .map(|entry| entry.status)
+    }
+
+    fn position(&self, request_id: u16) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry
+                .as_ref()
+                .is_some_and(|entry| entry.request_id == request_id)
+        })
+    }
+}
+
+pub(crate) fn status_to_result(status: u8) -> Result<()> {
+    match status {
+        STATUS_OK | STATUS_OK_MATCHED => Ok(()),
+        STATUS_ERR_DENIED => Err(Error::Deined),
+        STATUS_ERR_INCOMPATIBLE => Err(Error::Incompatible),
+        _ => Err(Error::Rejected(status)),
+    }
+}
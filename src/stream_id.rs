@@ -1,6 +1,7 @@
 const BEST_EFFORT_STREAM_THRESHOLD: u8 = 1;
 const RELIABLE_STREAM_THRESHOLD: u8 = 128;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StreamType {
     NoneStream,
     BestEffortStream,
@@ -8,11 +9,13 @@ pub enum StreamType {
     SharedMemoryStream,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StreamDirection {
     InputStream,
     OutputStream,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct StreamId {
     pub raw: u8,
     pub index: u8,
@@ -21,7 +24,7 @@ pub struct StreamId {
 }
 
 impl StreamId {
-    fn new(index: u8, type_u: StreamType, direction: StreamDirection) -> Self {
+    pub fn new(index: u8, type_u: StreamType, direction: StreamDirection) -> Self {
         StreamId {
             raw: match type_u {
                 StreamType::NoneStream => 0,
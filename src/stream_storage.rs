@@ -0,0 +1,118 @@
+use crate::input_best_effort_stream::InputBestEffortStream;
+use crate::input_reliable_stream::InputReliableStream;
+use crate::output_best_effort_stream::OutputBestEffortStream;
+use crate::output_reliable_stream::OutputReliableStream;
+use crate::stream_id::{StreamDirection, StreamId, StreamType};
+use crate::{
+    MAX_INPUT_BEST_EFFORT_STREAMS, MAX_INPUT_RELIABLE_STREAMS, MAX_OUTPUT_BEST_EFFORT_STREAMS,
+    MAX_OUTPUT_RELIABLE_STREAMS,
+};
+
+#[derive(Debug, Default)]
+pub(crate) struct StreamStorage<'storage> {
+    pub(crate) output_best_effort:
+        [Option<OutputBestEffortStream<'storage>>; MAX_OUTPUT_BEST_EFFORT_STREAMS],
+    pub(crate) output_reliable:
+        [Option<OutputReliableStream<'storage>>; MAX_OUTPUT_RELIABLE_STREAMS],
+    pub(crate) input_best_effort: [Option<InputBestEffortStream>; MAX_INPUT_BEST_EFFORT_STREAMS],
+    pub(crate) input_reliable: [Option<InputReliableStream<'storage>>; MAX_INPUT_RELIABLE_STREAMS],
+}
+
+impl<'storage> StreamStorage<'storage> {
+    pub(crate) fn add_output_best_effort_buffer(
+        &mut self,
+        buffer: &'storage mut [u8],
+        header_offset: usize,
+    ) -> Option<StreamId> {
+        let index = self.output_best_effort.iter().position(Option::is_none)?;
+        self.output_best_effort[index] = Some(OutputBestEffortStream::new(buffer, header_offset));
+        Some(StreamId::new(
+            index as u8,
+            StreamType::BestEffortStream,
+            StreamDirection::OutputStream,
+        ))
+    }
+
+    pub(crate) fn get_output_best_effort(
+        &mut self,
+        index: u8,
+    ) -> Option<&mut OutputBestEffortStream<'storage>> {
+        self.output_best_effort.get_mut(index as usize)?.as_mut()
+    }
+
+    pub(crate) fn add_output_reliable_buffer(
+        &mut self,
+        buffer: &'storage mut [u8],
+        history: u16,
+        header_offset: usize,
+    ) -> Option<StreamId> {
+        let index = self.output_reliable.iter().position(Option::is_none)?;
+        self.output_reliable[index] =
+            Some(OutputReliableStream::new(buffer, history, header_offset));
+        Some(StreamId::new(
+            index as u8,
+            StreamType::ReliableStream,
+            StreamDirection::OutputStream,
+        ))
+    }
+
+    pub(crate) fn get_output_reliable(
+        &mut self,
+        index: u8,
+    ) -> Option<&mut OutputReliableStream<'storage>> {
+        self.output_reliable.get_mut(index as usize)?.as_mut()
+    }
+
+    pub(crate) fn add_input_best_effort(&mut self) -> Option<StreamId> {
+        let index = self.input_best_effort.iter().position(Option::is_none)?;
+        self.input_best_effort[index] = Some(InputBestEffortStream::new());
+        Some(StreamId::new(
+            index as u8,
+            StreamType::BestEffortStream,
+            StreamDirection::InputStream,
+        ))
+    }
+
+    pub(crate) fn get_input_best_effort(
+        &mut self,
+        index: u8,
+    ) -> Option<&mut InputBestEffortStream> {
+        self.input_best_effort.get_mut(index as usize)?.as_mut()
+    }
+
+    pub(crate) fn add_input_reliable_buffer(
+        &mut self,
+        buffer: &'storage mut [u8],
+        history: u16,
+    ) -> Option<StreamId> {
+        let index = self.input_reliable.iter().position(Option::is_none)?;
+        self.input_reliable[index] = Some(InputReliableStream::new(buffer, history));
+        Some(StreamId::new(
+            index as u8,
+            StreamType::ReliableStream,
+            StreamDirection::InputStream,
+        ))
+    }
+
+    pub(crate) fn get_input_reliable(
+        &mut self,
+        index: u8,
+    ) -> Option<&mut InputReliableStream<'storage>> {
+        self.input_reliable.get_mut(index as usize)?.as_mut()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for stream in self.output_best_effort.iter_mut().flatten() {
+            stream.reset();
+        }
+        for stream in self.output_reliable.iter_mut().flatten() {
+            stream.reset();
+        }
+        for stream in self.input_best_effort.iter_mut().flatten() {
+            stream.reset();
+        }
+        for stream in self.input_reliable.iter_mut().flatten() {
+            stream.reset();
+        }
+    }
+}
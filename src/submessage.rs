@@ -4,10 +4,13 @@ use serde::{
     Deserialize,
 };
 
-use crate::micro_cdr;
+use crate::{micro_cdr, Endianness};
 
 pub const SUBHEADER_SIZE: usize = 4;
 
+/// Flag bit set when the submessage payload is encoded in little endian.
+pub const FLAG_ENDIANNESS: u8 = 0x01;
+
 ///
 /// 0       4       8               16               24               31
 /// +-------+-------+----------------+----------------+----------------+
@@ -91,8 +94,8 @@ impl Serialize for SubMessageHeader {
 
         let flags = flags
             | match micro_cdr::NATIVE_ENDIANNESS {
-                crate::Endianness::LittleEndianness => 1u8,
-                crate::Endianness::BigEndianness => 0u8,
+                Endianness::LittleEndianness => FLAG_ENDIANNESS,
+                Endianness::BigEndianness => 0u8,
             };
 
         let mut s = serializer.serialize_tuple(0)?;
@@ -166,7 +169,12 @@ impl<'de> Deserialize<'de> for SubMessageHeader {
                     13 => Fragment(len, (flag & 0b0000_0010) != 0),
                     14 => TimeStamp(len),
                     15 => TimeStampReply(len),
-                    _ => unreachable!(),
+                    _ => {
+                        return Err(de::Error::invalid_value(
+                            de::Unexpected::Unsigned(id as u64),
+                            &self,
+                        ))
+                    }
                 };
                 Ok(result)
             }
@@ -177,6 +185,30 @@ impl<'de> Deserialize<'de> for SubMessageHeader {
 }
 
 impl SubMessageHeader {
+    /// Length of the payload following the header.
+    pub fn payload_len(&self) -> u16 {
+        use SubMessageHeader::*;
+
+        match *self {
+            CreateClient(len)
+            | Create(len, _, _)
+            | GetInfo(len)
+            | Delete(len)
+            | StatusAgent(len)
+            | Status(len)
+            | Info(len)
+            | WriteData(len, _)
+            | ReadData(len)
+            | Data(len, _)
+            | AckNack(len)
+            | HeartBeat(len)
+            | Reset(len)
+            | Fragment(len, _)
+            | TimeStamp(len)
+            | TimeStampReply(len) => len,
+        }
+    }
+
     pub fn to_slice(self, buf: &mut [u8]) -> crate::error::Result<usize> {
         let mut ucdr = micro_cdr::Encoder::new(buf);
         self.serialize(&mut ucdr)?;
@@ -188,3 +220,72 @@ impl SubMessageHeader {
         SubMessageHeader::deserialize(&mut ucdr)
     }
 }
+
+/// A submessage of a received message body, with the raw flags of its header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubMessage<'a> {
+    pub header: SubMessageHeader,
+    pub flags: u8,
+    pub payload: &'a [u8],
+}
+
+impl SubMessage<'_> {
+    /// Endianness the payload is encoded in, given by its flags.
+    pub fn endianness(&self) -> Endianness {
+        if self.flags & FLAG_ENDIANNESS != 0 {
+            Endianness::LittleEndianness
+        } else {
+            Endianness::BigEndianness
+        }
+    }
+}
+
+///
+/// Iterator over the submessages of a received message body. Submessages are
+/// aligned to 4 bytes, the iteration stops at the first malformed one.
+///
+#[derive(Debug)]
+pub struct SubMessages<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SubMessages<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        SubMessages { buf, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for SubMessages<'a> {
+    type Item = crate::error::Result<SubMessage<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos + SUBHEADER_SIZE > self.buf.len() {
+            return None;
+        }
+
+        let begin = self.pos;
+        // nothing else can be read after a malformed submessage
+        self.pos = self.buf.len();
+
+        let header = match SubMessageHeader::from_slice(&self.buf[begin..]) {
+            Ok(header) => header,
+            Err(e) => return Some(Err(e)),
+        };
+        let flags = self.buf[begin + 1];
+
+        let payload_begin = begin + SUBHEADER_SIZE;
+        let payload_end = payload_begin + header.payload_len() as usize;
+        let payload = match self.buf.get(payload_begin..payload_end) {
+            Some(payload) => payload,
+            None => return Some(Err(crate::error::Error::BufferNotEnough)),
+        };
+
+        self.pos = (payload_end + 3) & !3;
+        Some(Ok(SubMessage {
+            header,
+            flags,
+            payload,
+        }))
+    }
+}
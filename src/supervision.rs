@@ -0,0 +1,199 @@
+use crate::object_id::ObjectId;
+use crate::representation::CreationMode;
+use crate::stream_id::StreamId;
+use crate::MAX_SUPERVISED_ENTITIES;
+
+///
+/// What [`Session::supervise`] found out about the agent.
+///
+/// [`Session::supervise`]: crate::session::Session::supervise
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AgentState {
+    /// The agent answered and the session is up.
+    Alive,
+    /// The agent did not answer the last pings, whose number is given. The session is
+    /// lost once it reaches the configured limit.
+    Unreachable(usize),
+    /// The agent answered after the session was lost and it was created again. True
+    /// when every entity replayed was created successfully.
+    Reestablished(bool),
+}
+
+/// A CREATE kept to be replayed, its payload is in the buffer of the supervisor.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Creation {
+    pub(crate) stream_id: StreamId,
+    pub(crate) object_id: ObjectId,
+    pub(crate) parent_id: Option<ObjectId>,
+    pub(crate) mode: CreationMode,
+    start: usize,
+    len: usize,
+}
+
+///
+/// Keeps track of the pings answered by the agent and of the CREATE of the entities
+/// written while the supervision is enabled, in the order they were written so the
+/// parents are created again before their children.
+///
+#[derive(Debug, Default)]
+pub(crate) struct Supervisor<'a> {
+    buffer: Option<&'a mut [u8]>,
+    creations: [Option<Creation>; MAX_SUPERVISED_ENTITIES],
+    used: usize,
+    max_failed_pings: usize,
+    failed_pings: usize,
+    lost: bool,
+}
+
+impl<'a> Supervisor<'a> {
+    pub(crate) fn enable(&mut self, buffer: &'a mut [u8], max_failed_pings: usize) {
+        *self = Supervisor {
+            buffer: Some(buffer),
+            max_failed_pings,
+            ..Default::default()
+        };
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.buffer.is_some()
+    }
+
+    /// Counts a ping left unanswered and returns how many there were in a row.
+    pub(crate) fn ping_failed(&mut self) -> usize {
+        self.failed_pings += 1;
+        if self.failed_pings >= self.max_failed_pings {
+            self.lost = true;
+        }
+        self.failed_pings
+    }
+
+    /// Counts an answered ping, returns true when the session was lost in between.
+    pub(crate) fn ping_answered(&mut self) -> bool {
+        self.failed_pings = 0;
+        self.lost
+    }
+
+    /// The agent forgot about the session.
+    pub(crate) fn agent_reset(&mut self) {
+        if self.is_enabled() {
+            self.lost = true;
+        }
+    }
+
+    pub(crate) fn reestablished(&mut self) {
+        self.lost = false;
+    }
+
+    /// Reserves `len` bytes for the CREATE payload of `object_id`, replacing the one
+    /// it already had in place. Returns none when there is no room left for it.
+    pub(crate) fn record(
+        &mut self,
+        stream_id: StreamId,
+        object_id: ObjectId,
+        parent_id: Option<ObjectId>,
+        mode: CreationMode,
+        len: usize,
+    ) -> Option<&mut [u8]> {
+        let buffer = self.buffer.as_deref_mut()?;
+
+        let index = match self
+            .creations
+            .iter()
+            .position(|creation| matches!(creation, Some(c) if c.object_id == object_id))
+        {
+            Some(index) => index,
+            None => {
+                let index = self.creations.iter().position(Option::is_none)?;
+                self.creations[index] = Some(Creation {
+                    stream_id,
+                    object_id,
+                    parent_id,
+                    mode,
+                    start: self.used,
+                    len: 0,
+                });
+                index
+            }
+        };
+
+        let creation = self.creations[index].as_mut()?;
+        let end = creation.start + creation.len;
+        if self.used - creation.len + len > buffer.len() {
+            if creation.len == 0 {
+                self.creations[index] = None;
+            }
+            return None;
+        }
+
+        // the payloads of the following creations are moved to fit the new one
+        let new_end = creation.start + len;
+        buffer.copy_within(end..self.used, new_end);
+        self.used = self.used - creation.len + len;
+        *creation = Creation {
+            stream_id,
+            object_id,
+            parent_id,
+            mode,
+            start: creation.start,
+            len,
+        };
+        for other in self.creations[index + 1..].iter_mut().flatten() {
+            other.start = other.start + new_end - end;
+        }
+
+        Some(&mut buffer[new_end - len..new_end])
+    }
+
+    /// Forgets the creation of `object_id` and of the entities it contains, which the
+    /// agent deletes with it.
+    pub(crate) fn remove(&mut self, object_id: ObjectId) {
+        let mut removed = [None; MAX_SUPERVISED_ENTITIES];
+        removed[0] = Some(object_id);
+        let mut count = 1;
+
+        let mut index = 0;
+        while index < self.creations.len() {
+            let creation = match self.creations[index] {
+                Some(creation) => creation,
+                None => break,
+            };
+
+            let parent_removed = removed[..count]
+                .iter()
+                .flatten()
+                .any(|&id| Some(id) == creation.parent_id);
+            if creation.object_id != object_id && !parent_removed {
+                index += 1;
+                continue;
+            }
+
+            if count < removed.len() {
+                removed[count] = Some(creation.object_id);
+                count += 1;
+            }
+
+            if let Some(buffer) = self.buffer.as_deref_mut() {
+                buffer.copy_within(creation.start + creation.len..self.used, creation.start);
+            }
+            self.used -= creation.len;
+            self.creations[index..].rotate_left(1);
+            let last = self.creations.len() - 1;
+            self.creations[last] = None;
+            for other in self.creations[index..].iter_mut().flatten() {
+                other.start -= creation.len;
+            }
+        }
+    }
+
+    /// The creations kept, in the order they have to be replayed, with their payload.
+    pub(crate) fn creations(&self) -> impl Iterator<Item = (&Creation, &[u8])> {
+        let buffer = self.buffer.as_deref().unwrap_or_default();
+        self.creations.iter().flatten().map(move |creation| {
+            (
+                creation,
+                &buffer[creation.start..creation.start + creation.len],
+            )
+        })
+    }
+}
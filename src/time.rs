@@ -1,3 +1,9 @@
 pub trait Clock {
     fn now(&mut self) -> i32;
+
+    /// Same clock as [`Clock::now`] in nanoseconds, for the platforms with a finer
+    /// resolution than milliseconds.
+    fn now_nanos(&mut self) -> i64 {
+        self.now() as i64 * 1_000_000
+    }
 }
@@ -1,6 +1,9 @@
+use crate::data::{DataRepresentation, DeliveryControl};
 use crate::error;
 use crate::micro_cdr;
-use crate::submessage::SubMessageHeader;
+use crate::object_id::{ObjectId, ObjectKind};
+use crate::representation::ObjectRepresentation;
+use crate::submessage::{DataFormat, SubMessageHeader};
 use serde::de::Visitor;
 use serde::ser::SerializeTuple;
 use serde::Deserialize;
@@ -24,10 +27,36 @@ const UXR_PROPERTY_SEQUENCE_MAX: usize = 1;
 #[cfg(not(any(feature = "hard-liveliness-check", feature = "profile-shared-memory")))]
 const UXR_PROPERTY_SEQUENCE_MAX: usize = 1;
 
+pub const XRCE_COOKIE: XrceCookie = [b'X', b'R', b'C', b'E'];
+pub const XRCE_VERSION_MAJOR: u8 = 0x01;
+pub const XRCE_VERSION_MINOR: u8 = 0x00;
+pub const XRCE_VENDOR_ID: XrceVendorId = [0x01, 0x0F];
+
+pub const OBJECTID_CLIENT: ObjectId = ObjectId::new(0xFFF, ObjectKind::Client);
+pub const REQUEST_LOGOUT: RequestId = [0x00, 0x02];
+pub const REQUEST_GET_INFO: RequestId = [0x00, 0x09];
+pub const OBJECTID_AGENT: ObjectId = ObjectId::new(0xFFF, ObjectKind::Agent);
+
+pub const INFO_CONFIGURATION: u32 = 0x01;
+pub const INFO_ACTIVITY: u32 = 0x02;
+
+pub const STATUS_OK: u8 = 0x00;
+pub const STATUS_OK_MATCHED: u8 = 0x01;
+pub const STATUS_ERR_DDS_ERROR: u8 = 0x80;
+pub const STATUS_ERR_MISMATCH: u8 = 0x81;
+pub const STATUS_ERR_ALREADY_EXISTS: u8 = 0x82;
+pub const STATUS_ERR_DENIED: u8 = 0x83;
+pub const STATUS_ERR_UNKNOWN_REFERENCE: u8 = 0x84;
+pub const STATUS_ERR_INVALID_DATA: u8 = 0x85;
+pub const STATUS_ERR_INCOMPATIBLE: u8 = 0x86;
+pub const STATUS_ERR_RESOURCES: u8 = 0x87;
+pub const STATUS_ERR_NOVALUE: u8 = 0x88;
+
 type XrceCookie = [u8; 4];
 type XrceVersion = [u8; 2];
 type XrceVendorId = [u8; 2];
 type ClientKey = [u8; 4];
+type RequestId = [u8; 2];
 
 #[allow(non_camel_case_types)]
 #[derive(Debug)]
@@ -197,3 +226,856 @@ impl<'a> CREATE_CLIENT_Payload<'a> {
         CREATE_CLIENT_Payload::deserialize(&mut ucdr)
     }
 }
+
+///
+/// 0                8               16
+/// +----------------+----------------+
+/// |     status     | implementation |
+/// +----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultStatus {
+    pub status: u8,
+    pub implementation_status: u8,
+}
+
+impl<'de> Deserialize<'de> for ResultStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = ResultStatus;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(ResultStatus {
+                    status: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    implementation_status: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct ResultStatus")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 2, VisitorInside)
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct AGENT_Representation {
+    pub xrce_cookie: XrceCookie,
+    pub xrce_version: XrceVersion,
+    pub xrce_vendor_id: XrceVendorId,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct STATUS_AGENT_Payload {
+    pub result: ResultStatus,
+    pub agent_info: AGENT_Representation,
+}
+
+impl<'de> Deserialize<'de> for STATUS_AGENT_Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = STATUS_AGENT_Payload;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                let result = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let agent_info = AGENT_Representation {
+                    xrce_cookie: [
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(3, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(4, &self))?,
+                    ],
+                    xrce_version: [
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(5, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(6, &self))?,
+                    ],
+                    xrce_vendor_id: [
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(7, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(8, &self))?,
+                    ],
+                };
+
+                // the agent properties are not used by the client, they are only skipped
+                let has_properties: bool = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(9, &self))?;
+                if has_properties {
+                    let len: u32 = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(10, &self))?;
+                    for _ in 0..len {
+                        let _: &str = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(11, &self))?;
+                        let _: &str = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(11, &self))?;
+                    }
+                }
+
+                Ok(STATUS_AGENT_Payload { result, agent_info })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct STATUS_AGENT_Payload")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", usize::MAX, VisitorInside)
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |             requestId           |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaseObjectRequest {
+    pub request_id: RequestId,
+    pub object_id: ObjectId,
+}
+
+impl Serialize for BaseObjectRequest {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.request_id[0])?;
+        s.serialize_element(&self.request_id[1])?;
+        s.serialize_element(&self.object_id)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for BaseObjectRequest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = BaseObjectRequest;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(BaseObjectRequest {
+                    request_id: [
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    ],
+                    object_id: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct BaseObjectRequest")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 4, VisitorInside)
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub struct DELETE_Payload(pub BaseObjectRequest);
+
+pub(crate) const BASE_OBJECT_REQUEST_SIZE: usize = 4;
+const DELETE_PAYLOAD_SIZE: usize = BASE_OBJECT_REQUEST_SIZE;
+
+impl DELETE_Payload {
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        SubMessageHeader::Delete(DELETE_PAYLOAD_SIZE as u16).serialize(&mut ucdr)?;
+        self.0.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// Description of the entity to create, its kind is written first and followed by
+/// its representation and the fields specific to that kind.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectVariant<'a> {
+    Participant {
+        representation: ObjectRepresentation<'a>,
+        domain_id: i16,
+    },
+    Topic {
+        representation: ObjectRepresentation<'a>,
+        participant_id: ObjectId,
+    },
+    Publisher {
+        representation: ObjectRepresentation<'a>,
+        participant_id: ObjectId,
+    },
+    DataWriter {
+        representation: ObjectRepresentation<'a>,
+        publisher_id: ObjectId,
+    },
+    Subscriber {
+        representation: ObjectRepresentation<'a>,
+        participant_id: ObjectId,
+    },
+    DataReader {
+        representation: ObjectRepresentation<'a>,
+        subscriber_id: ObjectId,
+    },
+    Requester {
+        representation: ObjectRepresentation<'a>,
+        participant_id: ObjectId,
+    },
+    Replier {
+        representation: ObjectRepresentation<'a>,
+        participant_id: ObjectId,
+    },
+}
+
+impl<'a> ObjectVariant<'a> {
+    /// Serialized size when written at `offset`, including its alignment.
+    fn size(&self, offset: usize) -> usize {
+        // kind
+        let offset = self.representation().size(offset + 1);
+        match *self {
+            // domain id aligned to 2 bytes
+            ObjectVariant::Participant { .. } => ((offset + 1) & !1) + 2,
+            // parent object id
+            _ => offset + 2,
+        }
+    }
+
+    fn kind(&self) -> ObjectKind {
+        match *self {
+            ObjectVariant::Participant { .. } => ObjectKind::Participant,
+            ObjectVariant::Topic { .. } => ObjectKind::Topic,
+            ObjectVariant::Publisher { .. } => ObjectKind::Publisher,
+            ObjectVariant::DataWriter { .. } => ObjectKind::DataWriter,
+            ObjectVariant::Subscriber { .. } => ObjectKind::Subscriber,
+            ObjectVariant::DataReader { .. } => ObjectKind::DataReader,
+            ObjectVariant::Requester { .. } => ObjectKind::Requester,
+            ObjectVariant::Replier { .. } => ObjectKind::Replier,
+        }
+    }
+
+    fn representation(&self) -> ObjectRepresentation<'a> {
+        match *self {
+            ObjectVariant::Participant { representation, .. }
+            | ObjectVariant::Topic { representation, .. }
+            | ObjectVariant::Publisher { representation, .. }
+            | ObjectVariant::DataWriter { representation, .. }
+            | ObjectVariant::Subscriber { representation, .. }
+            | ObjectVariant::DataReader { representation, .. }
+            | ObjectVariant::Requester { representation, .. }
+            | ObjectVariant::Replier { representation, .. } => representation,
+        }
+    }
+
+    /// Returns false when the agent can't create the object from its representation:
+    /// there are no references to publishers or subscribers, only their XML or binary
+    /// representation.
+    pub(crate) fn has_valid_representation(&self) -> bool {
+        !matches!(
+            *self,
+            ObjectVariant::Publisher {
+                representation: ObjectRepresentation::Ref(_),
+                ..
+            } | ObjectVariant::Subscriber {
+                representation: ObjectRepresentation::Ref(_),
+                ..
+            }
+        )
+    }
+
+    /// Entity the object is created in, none for a participant.
+    pub(crate) fn parent_id(&self) -> Option<ObjectId> {
+        match *self {
+            ObjectVariant::Participant { .. } => None,
+            ObjectVariant::Topic { participant_id, .. }
+            | ObjectVariant::Publisher { participant_id, .. }
+            | ObjectVariant::Subscriber { participant_id, .. }
+            | ObjectVariant::Requester { participant_id, .. }
+            | ObjectVariant::Replier { participant_id, .. } => Some(participant_id),
+            ObjectVariant::DataWriter { publisher_id, .. } => Some(publisher_id),
+            ObjectVariant::DataReader { subscriber_id, .. } => Some(subscriber_id),
+        }
+    }
+}
+
+impl Serialize for ObjectVariant<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&(self.kind() as u8))?;
+        s.serialize_element(&self.representation())?;
+        if let ObjectVariant::Participant { domain_id, .. } = *self {
+            s.serialize_element(&domain_id)?;
+        }
+        if let Some(parent_id) = self.parent_id() {
+            s.serialize_element(&parent_id)?;
+        }
+        s.end()
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |             requestId           |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |      kind      |    representation and kind specific fields ...   |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct CREATE_Payload<'a> {
+    pub base: BaseObjectRequest,
+    pub object_representation: ObjectVariant<'a>,
+}
+
+impl CREATE_Payload<'_> {
+    pub fn size(&self) -> usize {
+        self.object_representation.size(BASE_OBJECT_REQUEST_SIZE)
+    }
+
+    /// Writes the payload only, the submessage header carries the creation mode
+    /// and is written by the output stream.
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        self.base.serialize(&mut ucdr)?;
+        self.object_representation.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |             requestId           |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |           data in the format of the submessage flags ...          |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct WRITE_DATA_Payload<'a> {
+    pub base: BaseObjectRequest,
+    pub data: DataRepresentation<'a>,
+}
+
+impl WRITE_DATA_Payload<'_> {
+    pub fn size(&self) -> usize {
+        self.data.size(BASE_OBJECT_REQUEST_SIZE)
+    }
+
+    /// Writes the payload only, the submessage header carries the data format
+    /// and is written by the output stream.
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        self.base.serialize(&mut ucdr)?;
+        self.data.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |             requestId           |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |  preferredId   |   dataFormat   |  filter flag   |  control flag  |
+/// +----------------+----------------+----------------+----------------+
+/// |                deliveryControl (if control flag) ...              |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct READ_DATA_Payload {
+    pub base: BaseObjectRequest,
+    pub preferred_stream_id: u8,
+    pub data_format: DataFormat,
+    pub delivery_control: Option<DeliveryControl>,
+}
+
+impl READ_DATA_Payload {
+    pub fn size(&self) -> usize {
+        match self.delivery_control {
+            Some(_) => READ_DATA_PAYLOAD_SIZE + DELIVERY_CONTROL_SIZE,
+            None => READ_DATA_PAYLOAD_SIZE,
+        }
+    }
+
+    /// Writes the payload only, the submessage header is written by the output stream.
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        self.base.serialize(&mut ucdr)?;
+        self.preferred_stream_id.serialize(&mut ucdr)?;
+        // the same values as the format in the flags of the DATA submessages
+        ((self.data_format as u8) << 1).serialize(&mut ucdr)?;
+        // content filter expressions are not supported
+        false.serialize(&mut ucdr)?;
+        match self.delivery_control {
+            Some(delivery_control) => {
+                true.serialize(&mut ucdr)?;
+                delivery_control.serialize(&mut ucdr)?;
+            }
+            None => false.serialize(&mut ucdr)?,
+        }
+
+        Ok(ucdr.finalize())
+    }
+}
+
+const READ_DATA_PAYLOAD_SIZE: usize = BASE_OBJECT_REQUEST_SIZE + 4;
+const DELIVERY_CONTROL_SIZE: usize = 8;
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |             requestId           |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |     status     | implementation |
+/// +----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct STATUS_Payload {
+    pub related_request: BaseObjectRequest,
+    pub result: ResultStatus,
+}
+
+impl<'de> Deserialize<'de> for STATUS_Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = STATUS_Payload;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(STATUS_Payload {
+                    related_request: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    result: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct STATUS_Payload")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 2, VisitorInside)
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |        firstUnackedSeqNr        |         lastUnackedSeqNr        |
+/// +----------------+----------------+----------------+----------------+
+/// |    streamId    |
+/// +----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct HEARTBEAT_Payload {
+    pub first_unacked_seq_nr: u16,
+    pub last_unacked_seq_nr: u16,
+    pub stream_id: u8,
+}
+
+impl Serialize for HEARTBEAT_Payload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.first_unacked_seq_nr)?;
+        s.serialize_element(&self.last_unacked_seq_nr)?;
+        s.serialize_element(&self.stream_id)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for HEARTBEAT_Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = HEARTBEAT_Payload;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(HEARTBEAT_Payload {
+                    first_unacked_seq_nr: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    last_unacked_seq_nr: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    stream_id: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct HEARTBEAT_Payload")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 3, VisitorInside)
+    }
+}
+
+pub const HEARTBEAT_PAYLOAD_SIZE: usize = 5;
+
+impl HEARTBEAT_Payload {
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        SubMessageHeader::HeartBeat(HEARTBEAT_PAYLOAD_SIZE as u16).serialize(&mut ucdr)?;
+        self.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |        firstUnackedSeqNum       |            nackBitmap           |
+/// +----------------+----------------+----------------+----------------+
+/// |    streamId    |
+/// +----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct ACKNACK_Payload {
+    pub first_unacked_seq_num: u16,
+    pub nack_bitmap: [u8; 2],
+    pub stream_id: u8,
+}
+
+impl Serialize for ACKNACK_Payload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.first_unacked_seq_num)?;
+        s.serialize_element(&self.nack_bitmap[0])?;
+        s.serialize_element(&self.nack_bitmap[1])?;
+        s.serialize_element(&self.stream_id)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ACKNACK_Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = ACKNACK_Payload;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(ACKNACK_Payload {
+                    first_unacked_seq_num: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    nack_bitmap: [
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                    ],
+                    stream_id: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(3, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct ACKNACK_Payload")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 4, VisitorInside)
+    }
+}
+
+pub const ACKNACK_PAYLOAD_SIZE: usize = 5;
+
+impl ACKNACK_Payload {
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        SubMessageHeader::AckNack(ACKNACK_PAYLOAD_SIZE as u16).serialize(&mut ucdr)?;
+        self.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |                              seconds                              |
+/// +----------------+----------------+----------------+----------------+
+/// |                            nanoseconds                            |
+/// +----------------+----------------+----------------+----------------+
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    pub seconds: i32,
+    pub nanoseconds: u32,
+}
+
+impl Time {
+    pub fn from_nanos(nanos: i64) -> Self {
+        Time {
+            seconds: nanos.div_euclid(1_000_000_000) as i32,
+            nanoseconds: nanos.rem_euclid(1_000_000_000) as u32,
+        }
+    }
+
+    pub fn to_nanos(self) -> i64 {
+        self.seconds as i64 * 1_000_000_000 + self.nanoseconds as i64
+    }
+}
+
+impl Serialize for Time {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_tuple(0)?;
+        s.serialize_element(&self.seconds)?;
+        s.serialize_element(&self.nanoseconds)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = Time;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(Time {
+                    seconds: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    nanoseconds: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct Time")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 2, VisitorInside)
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |                     transmitTimestamp (8 bytes)                   |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct TIMESTAMP_Payload {
+    pub transmit_timestamp: Time,
+}
+
+pub const TIMESTAMP_PAYLOAD_SIZE: usize = 8;
+
+impl TIMESTAMP_Payload {
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        SubMessageHeader::TimeStamp(TIMESTAMP_PAYLOAD_SIZE as u16).serialize(&mut ucdr)?;
+        self.transmit_timestamp.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
+
+///
+/// Answer of the agent to a TIMESTAMP: when it sent the reply, when it received the
+/// TIMESTAMP, and the transmit timestamp of the TIMESTAMP itself.
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |                     transmitTimestamp (8 bytes)                   |
+/// +----------------+----------------+----------------+----------------+
+/// |                      receiveTimestamp (8 bytes)                   |
+/// +----------------+----------------+----------------+----------------+
+/// |                     originateTimestamp (8 bytes)                  |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct TIMESTAMP_REPLY_Payload {
+    pub transmit_timestamp: Time,
+    pub receive_timestamp: Time,
+    pub originate_timestamp: Time,
+}
+
+impl<'de> Deserialize<'de> for TIMESTAMP_REPLY_Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VisitorInside;
+
+        impl<'de> Visitor<'de> for VisitorInside {
+            type Value = TIMESTAMP_REPLY_Payload;
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de;
+                Ok(TIMESTAMP_REPLY_Payload {
+                    transmit_timestamp: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+                    receive_timestamp: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    originate_timestamp: seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                })
+            }
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("struct TIMESTAMP_REPLY_Payload")
+            }
+        }
+
+        deserializer.deserialize_tuple_struct("", 3, VisitorInside)
+    }
+}
+
+///
+/// 0                8               16               24               31
+/// +----------------+----------------+----------------+----------------+
+/// |            requestId            |             objectId            |
+/// +----------------+----------------+----------------+----------------+
+/// |                              infoMask                             |
+/// +----------------+----------------+----------------+----------------+
+///
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub struct GET_INFO_Payload {
+    pub base: BaseObjectRequest,
+    pub info_mask: u32,
+}
+
+pub const GET_INFO_PAYLOAD_SIZE: usize = BASE_OBJECT_REQUEST_SIZE + 4;
+
+impl GET_INFO_Payload {
+    pub fn to_slice(&self, buf: &mut [u8]) -> error::Result<usize> {
+        let mut ucdr = micro_cdr::Encoder::new(buf);
+
+        SubMessageHeader::GetInfo(GET_INFO_PAYLOAD_SIZE as u16).serialize(&mut ucdr)?;
+        self.base.serialize(&mut ucdr)?;
+        self.info_mask.serialize(&mut ucdr)?;
+
+        Ok(ucdr.finalize())
+    }
+}
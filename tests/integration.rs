@@ -187,4 +187,18 @@ mod tests {
         serde::Serializer::serialize_bool(&mut writer, v).unwrap();
         assert_eq!([0x01], buf[0..1]);
     }
+
+    #[test]
+    fn deserialize_string() {
+        use serde::Deserialize;
+
+        let buf = [0x03, 0x00, 0x00, 0x00, b'a', b'b', 0x00];
+        let mut reader = xrce_client_rs::micro_cdr::Decoder::new(&buf);
+        assert_eq!("ab", <&str>::deserialize(&mut reader).unwrap());
+
+        // without even the terminating null character
+        let buf = [0x00, 0x00, 0x00, 0x00];
+        let mut reader = xrce_client_rs::micro_cdr::Decoder::new(&buf);
+        assert!(<&str>::deserialize(&mut reader).is_err());
+    }
 }